@@ -0,0 +1,34 @@
+use anyhow::Result;
+use usls::{models::DepthAnything, Config, DataLoader, Pool};
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_timer(tracing_subscriber::fmt::time::ChronoLocal::rfc_3339())
+        .init();
+
+    // build a pool of models
+    let config = Config::depth_anything_v2_small().commit()?;
+    let pool = Pool::try_new(2, |_| DepthAnything::new(config.clone()))?;
+
+    // load
+    let xs = DataLoader::try_read_n(&["images/street.jpg", "images/bus.jpg"])?;
+
+    // run from multiple threads
+    let pool = &pool;
+    std::thread::scope(|s| -> Result<()> {
+        let handles: Vec<_> = xs
+            .chunks(1)
+            .map(|x| s.spawn(move || pool.get().forward(x)))
+            .collect();
+        for handle in handles {
+            let ys = handle.join().expect("Worker thread panicked")?;
+            println!("ys: {:?}", ys);
+        }
+        Ok(())
+    })?;
+
+    usls::perf(false);
+
+    Ok(())
+}
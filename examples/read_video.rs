@@ -22,6 +22,9 @@ fn main() -> anyhow::Result<()> {
     let dl = DataLoader::new(args.source.as_str())?
         .with_batch(1)
         // .with_nf_skip(1)
+        // .with_start(std::time::Duration::from_secs(600))
+        // .with_end(std::time::Duration::from_secs(1200))
+        // .with_sample_fps(2.0)
         // .with_progress_bar(true)
         .build()?;
 
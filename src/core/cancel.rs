@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+/// Why a run stopped before it finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interruption {
+    /// [`CancelToken::cancel`] was called.
+    Cancelled,
+    /// The deadline of the [`CancelToken`] passed.
+    TimedOut,
+}
+
+impl std::fmt::Display for Interruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::TimedOut => write!(f, "timed out"),
+        }
+    }
+}
+
+impl std::error::Error for Interruption {}
+
+/// Cooperative cancellation with an optional deadline.
+///
+/// Clones share the cancelled flag, so a token handed to a model can be cancelled from
+/// another thread. The deadline belongs to each clone: [`CancelToken::with_timeout`] on a
+/// shared token gives one request its own time budget.
+///
+/// Long-running loops check [`CancelToken::interrupted`] between steps. Generation loops
+/// then return what they have so far, with the reason in [`crate::Y::interruption`], and
+/// [`crate::Engine`] fails with an [`Interruption`] error, which generation loops catch with
+/// [`crate::run_or_break`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the instant after which the token counts as timed out.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline to `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Cancels this token and all its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns why the work should stop, `None` to keep going.
+    pub fn interrupted(&self) -> Option<Interruption> {
+        if self.is_cancelled() {
+            Some(Interruption::Cancelled)
+        } else if self.deadline.is_some_and(|x| Instant::now() >= x) {
+            Some(Interruption::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    /// Fails with the [`Interruption`] if the work should stop.
+    pub fn check(&self) -> Result<(), Interruption> {
+        match self.interrupted() {
+            Some(x) => Err(x),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Unwraps the result of an engine run inside a generation loop.
+///
+/// If the run failed with an [`Interruption`], it is stored in `$interruption` and the loop
+/// breaks, keeping what was generated so far. Other errors are returned.
+#[macro_export]
+macro_rules! run_or_break {
+    ($run:expr, $interruption:ident) => {
+        match $run {
+            Ok(x) => x,
+            Err(err) => match err.downcast_ref::<$crate::Interruption>() {
+                Some(&x) => {
+                    $interruption = Some(x);
+                    break;
+                }
+                None => return Err(err),
+            },
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests_cancel {
+    use super::{CancelToken, Interruption};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let shared = token.clone().with_timeout(Duration::from_secs(60));
+        assert_eq!(shared.interrupted(), None);
+        token.cancel();
+        assert_eq!(shared.interrupted(), Some(Interruption::Cancelled));
+        assert!(shared.check().is_err());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let token = CancelToken::new().with_deadline(Instant::now());
+        assert_eq!(token.interrupted(), Some(Interruption::TimedOut));
+        assert_eq!(CancelToken::new().interrupted(), None);
+        assert!(CancelToken::new().check().is_ok());
+    }
+
+    #[test]
+    fn test_run_or_break() -> anyhow::Result<()> {
+        let runs = [
+            Ok(1),
+            Ok(2),
+            Err(anyhow::Error::new(Interruption::TimedOut)),
+            Ok(3),
+        ];
+        let mut ys = vec![];
+        let mut interruption = None;
+        for run in runs {
+            ys.push(run_or_break!(run, interruption));
+        }
+        assert_eq!(ys, [1, 2]);
+        assert_eq!(interruption, Some(Interruption::TimedOut));
+
+        let mut interruption = None;
+        let err = (|| -> anyhow::Result<()> {
+            loop {
+                run_or_break!(Err(anyhow::anyhow!("failed")), interruption);
+            }
+            Ok(())
+        })()
+        .unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+        assert_eq!(interruption, None);
+        Ok(())
+    }
+}
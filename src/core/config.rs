@@ -1,4 +1,7 @@
 use aksr::Builder;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 use crate::{
     models::{SamKind, YOLOPredsFormat},
@@ -6,30 +9,50 @@ use crate::{
 };
 
 /// Configuration for model inference including engines, processors, and task settings.
-#[derive(Builder, Debug, Clone)]
+///
+/// Can be loaded from and saved to TOML or JSON files, see [`Config::from_file`].
+/// Missing keys take their default values, and unused engines are left out when saving.
+#[derive(Builder, Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     // Basics
-    pub name: &'static str,
+    pub name: String,
     pub version: Option<Version>,
     pub task: Option<Task>,
     pub scale: Option<Scale>,
 
     // Engines
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub model: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub visual: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub textual: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub encoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub decoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub visual_encoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub textual_encoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub visual_decoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub textual_decoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub textual_decoder_merged: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub size_encoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub size_decoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub coord_encoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub coord_decoder: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub visual_projection: ORTConfig,
+    #[serde(skip_serializing_if = "ORTConfig::is_unset")]
     pub textual_projection: ORTConfig,
 
     // Processor
@@ -116,6 +139,138 @@ impl Default for Config {
 }
 
 impl Config {
+    /// Loads a config from a `.toml` or `.json` file and validates it.
+    ///
+    /// Errors point to the offending key, e.g. `processor.resize_filter` or `class_confs[1]`.
+    pub fn from_file<P: AsRef<Path>>(p: P) -> Result<Self> {
+        let p = p.as_ref();
+        let s = std::fs::read_to_string(p)
+            .map_err(|err| anyhow::anyhow!("Failed to read config {}: {}", p.display(), err))?;
+        Self::from_str_with_format(&s, Self::file_format(p)?)
+            .map_err(|err| anyhow::anyhow!("Invalid config {}: {}", p.display(), err))
+    }
+
+    /// Saves the config to a `.toml` or `.json` file.
+    ///
+    /// In-memory model bytes and decryptors are not saved.
+    pub fn to_file<P: AsRef<Path>>(&self, p: P) -> Result<()> {
+        let p = p.as_ref();
+        let s = self.to_string_with_format(Self::file_format(p)?)?;
+        if let Some(dir) = p.parent().filter(|x| !x.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(p, s)
+            .map_err(|err| anyhow::anyhow!("Failed to write config {}: {}", p.display(), err))
+    }
+
+    /// Checks values that deserialize fine but make no sense.
+    pub fn validate(&self) -> Result<()> {
+        for (key, xs) in [
+            ("class_confs", &self.class_confs),
+            ("keypoint_confs", &self.keypoint_confs),
+            ("text_confs", &self.text_confs),
+        ] {
+            for (i, x) in xs.iter().enumerate() {
+                if !(0. ..=1.).contains(x) {
+                    anyhow::bail!("`{}[{}]`: {} is out of range [0, 1]", key, i, x);
+                }
+            }
+        }
+        if let Some(x) = self.iou {
+            if !(0. ..=1.).contains(&x) {
+                anyhow::bail!("`iou`: {} is out of range [0, 1]", x);
+            }
+        }
+        let (mean, std) = (&self.processor.image_mean, &self.processor.image_std);
+        if !mean.is_empty() && !std.is_empty() && mean.len() != std.len() {
+            anyhow::bail!(
+                "`processor.image_mean` and `processor.image_std`: lengths differ, {} vs {}",
+                mean.len(),
+                std.len()
+            );
+        }
+        if let Some(i) = std.iter().position(|x| *x == 0.) {
+            anyhow::bail!("`processor.image_std[{}]`: must not be 0", i);
+        }
+        for (name, engine) in self.engines() {
+            for (i, iiix) in engine.iiixs.iter().enumerate() {
+                let x = &iiix.x;
+                if !(x.min() <= x.opt() && x.opt() <= x.max()) {
+                    anyhow::bail!(
+                        "`{}.iiixs[{}].x`: expected min <= opt <= max, got ({}, {}, {})",
+                        name,
+                        i,
+                        x.min(),
+                        x.opt(),
+                        x.max()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn engines(&self) -> [(&'static str, &ORTConfig); 16] {
+        [
+            ("model", &self.model),
+            ("visual", &self.visual),
+            ("textual", &self.textual),
+            ("encoder", &self.encoder),
+            ("decoder", &self.decoder),
+            ("visual_encoder", &self.visual_encoder),
+            ("textual_encoder", &self.textual_encoder),
+            ("visual_decoder", &self.visual_decoder),
+            ("textual_decoder", &self.textual_decoder),
+            ("textual_decoder_merged", &self.textual_decoder_merged),
+            ("size_encoder", &self.size_encoder),
+            ("size_decoder", &self.size_decoder),
+            ("coord_encoder", &self.coord_encoder),
+            ("coord_decoder", &self.coord_decoder),
+            ("visual_projection", &self.visual_projection),
+            ("textual_projection", &self.textual_projection),
+        ]
+    }
+
+    fn file_format(p: &Path) -> Result<&'static str> {
+        match p.extension().and_then(|x| x.to_str()) {
+            Some(x) if x.eq_ignore_ascii_case("toml") => Ok("toml"),
+            Some(x) if x.eq_ignore_ascii_case("json") => Ok("json"),
+            _ => anyhow::bail!(
+                "Unsupported config format: {}. Expected `.toml` or `.json`",
+                p.display()
+            ),
+        }
+    }
+
+    fn from_str_with_format(s: &str, format: &str) -> Result<Self> {
+        let config: Self = match format {
+            "toml" => serde_path_to_error::deserialize(toml::Deserializer::new(s))
+                .map_err(|err| Self::path_error(err.path(), err.inner()))?,
+            "json" => serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(s))
+                .map_err(|err| Self::path_error(err.path(), err.inner()))?,
+            x => anyhow::bail!("Unsupported config format: {}", x),
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn to_string_with_format(&self, format: &str) -> Result<String> {
+        match format {
+            "toml" => Ok(toml::to_string_pretty(self)?),
+            "json" => Ok(serde_json::to_string_pretty(self)?),
+            x => anyhow::bail!("Unsupported config format: {}", x),
+        }
+    }
+
+    fn path_error(path: &serde_path_to_error::Path, err: impl std::fmt::Display) -> anyhow::Error {
+        match path.to_string().as_str() {
+            "." => anyhow::anyhow!("{}", err),
+            path => anyhow::anyhow!("`{}`: {}", path, err),
+        }
+    }
+
     pub fn exclude_classes(mut self, xs: &[usize]) -> Self {
         self.classes_retained.clear();
         self.classes_excluded.extend_from_slice(xs);
@@ -147,7 +302,7 @@ impl Config {
         }
 
         fn try_commit(name: &str, mut m: ORTConfig) -> anyhow::Result<ORTConfig> {
-            if !m.file.is_empty() {
+            if !m.file.is_empty() || m.bytes.is_some() {
                 m = m.try_commit(name)?;
                 return Ok(m);
             }
@@ -155,22 +310,22 @@ impl Config {
             Ok(m)
         }
 
-        self.model = try_commit(self.name, self.model)?;
-        self.visual = try_commit(self.name, self.visual)?;
-        self.textual = try_commit(self.name, self.textual)?;
-        self.encoder = try_commit(self.name, self.encoder)?;
-        self.decoder = try_commit(self.name, self.decoder)?;
-        self.visual_encoder = try_commit(self.name, self.visual_encoder)?;
-        self.textual_encoder = try_commit(self.name, self.textual_encoder)?;
-        self.visual_decoder = try_commit(self.name, self.visual_decoder)?;
-        self.textual_decoder = try_commit(self.name, self.textual_decoder)?;
-        self.textual_decoder_merged = try_commit(self.name, self.textual_decoder_merged)?;
-        self.size_encoder = try_commit(self.name, self.size_encoder)?;
-        self.size_decoder = try_commit(self.name, self.size_decoder)?;
-        self.coord_encoder = try_commit(self.name, self.coord_encoder)?;
-        self.coord_decoder = try_commit(self.name, self.coord_decoder)?;
-        self.visual_projection = try_commit(self.name, self.visual_projection)?;
-        self.textual_projection = try_commit(self.name, self.textual_projection)?;
+        self.model = try_commit(&self.name, self.model)?;
+        self.visual = try_commit(&self.name, self.visual)?;
+        self.textual = try_commit(&self.name, self.textual)?;
+        self.encoder = try_commit(&self.name, self.encoder)?;
+        self.decoder = try_commit(&self.name, self.decoder)?;
+        self.visual_encoder = try_commit(&self.name, self.visual_encoder)?;
+        self.textual_encoder = try_commit(&self.name, self.textual_encoder)?;
+        self.visual_decoder = try_commit(&self.name, self.visual_decoder)?;
+        self.textual_decoder = try_commit(&self.name, self.textual_decoder)?;
+        self.textual_decoder_merged = try_commit(&self.name, self.textual_decoder_merged)?;
+        self.size_encoder = try_commit(&self.name, self.size_encoder)?;
+        self.size_decoder = try_commit(&self.name, self.size_decoder)?;
+        self.coord_encoder = try_commit(&self.name, self.coord_encoder)?;
+        self.coord_decoder = try_commit(&self.name, self.coord_decoder)?;
+        self.visual_projection = try_commit(&self.name, self.visual_projection)?;
+        self.textual_projection = try_commit(&self.name, self.textual_projection)?;
 
         Ok(self)
     }
@@ -196,6 +351,27 @@ impl Config {
         self
     }
 
+    pub fn with_io_binding_all(mut self, x: bool) -> Self {
+        self.visual = self.visual.with_io_binding(x);
+        self.textual = self.textual.with_io_binding(x);
+        self.model = self.model.with_io_binding(x);
+        self.encoder = self.encoder.with_io_binding(x);
+        self.decoder = self.decoder.with_io_binding(x);
+        self.visual_encoder = self.visual_encoder.with_io_binding(x);
+        self.textual_encoder = self.textual_encoder.with_io_binding(x);
+        self.visual_decoder = self.visual_decoder.with_io_binding(x);
+        self.textual_decoder = self.textual_decoder.with_io_binding(x);
+        self.textual_decoder_merged = self.textual_decoder_merged.with_io_binding(x);
+        self.size_encoder = self.size_encoder.with_io_binding(x);
+        self.size_decoder = self.size_decoder.with_io_binding(x);
+        self.coord_encoder = self.coord_encoder.with_io_binding(x);
+        self.coord_decoder = self.coord_decoder.with_io_binding(x);
+        self.visual_projection = self.visual_projection.with_io_binding(x);
+        self.textual_projection = self.textual_projection.with_io_binding(x);
+
+        self
+    }
+
     pub fn with_batch_size_all(mut self, batch_size: usize) -> Self {
         self.visual = self.visual.with_ixx(0, 0, batch_size.into());
         self.textual = self.textual.with_ixx(0, 0, batch_size.into());
@@ -240,6 +416,27 @@ impl Config {
         self
     }
 
+    pub fn with_devices_all(mut self, devices: &[crate::Device]) -> Self {
+        self.visual = self.visual.with_devices(devices);
+        self.textual = self.textual.with_devices(devices);
+        self.model = self.model.with_devices(devices);
+        self.encoder = self.encoder.with_devices(devices);
+        self.decoder = self.decoder.with_devices(devices);
+        self.visual_encoder = self.visual_encoder.with_devices(devices);
+        self.textual_encoder = self.textual_encoder.with_devices(devices);
+        self.visual_decoder = self.visual_decoder.with_devices(devices);
+        self.textual_decoder = self.textual_decoder.with_devices(devices);
+        self.textual_decoder_merged = self.textual_decoder_merged.with_devices(devices);
+        self.size_encoder = self.size_encoder.with_devices(devices);
+        self.size_decoder = self.size_decoder.with_devices(devices);
+        self.coord_encoder = self.coord_encoder.with_devices(devices);
+        self.coord_decoder = self.coord_decoder.with_devices(devices);
+        self.visual_projection = self.visual_projection.with_devices(devices);
+        self.textual_projection = self.textual_projection.with_devices(devices);
+
+        self
+    }
+
     pub fn with_dtype_all(mut self, dtype: crate::DType) -> Self {
         self.visual = self.visual.with_dtype(dtype);
         self.textual = self.textual.with_dtype(dtype);
@@ -325,6 +522,66 @@ impl Config {
         self
     }
 
+    pub fn with_operator_library_all(mut self, x: &str) -> Self {
+        self.visual = self.visual.with_operator_library(x);
+        self.textual = self.textual.with_operator_library(x);
+        self.model = self.model.with_operator_library(x);
+        self.encoder = self.encoder.with_operator_library(x);
+        self.decoder = self.decoder.with_operator_library(x);
+        self.visual_encoder = self.visual_encoder.with_operator_library(x);
+        self.textual_encoder = self.textual_encoder.with_operator_library(x);
+        self.visual_decoder = self.visual_decoder.with_operator_library(x);
+        self.textual_decoder = self.textual_decoder.with_operator_library(x);
+        self.textual_decoder_merged = self.textual_decoder_merged.with_operator_library(x);
+        self.size_encoder = self.size_encoder.with_operator_library(x);
+        self.size_decoder = self.size_decoder.with_operator_library(x);
+        self.coord_encoder = self.coord_encoder.with_operator_library(x);
+        self.coord_decoder = self.coord_decoder.with_operator_library(x);
+        self.visual_projection = self.visual_projection.with_operator_library(x);
+        self.textual_projection = self.textual_projection.with_operator_library(x);
+        self
+    }
+
+    pub fn with_graph_cache_all(mut self, x: &str) -> Self {
+        self.visual = self.visual.with_graph_cache(x);
+        self.textual = self.textual.with_graph_cache(x);
+        self.model = self.model.with_graph_cache(x);
+        self.encoder = self.encoder.with_graph_cache(x);
+        self.decoder = self.decoder.with_graph_cache(x);
+        self.visual_encoder = self.visual_encoder.with_graph_cache(x);
+        self.textual_encoder = self.textual_encoder.with_graph_cache(x);
+        self.visual_decoder = self.visual_decoder.with_graph_cache(x);
+        self.textual_decoder = self.textual_decoder.with_graph_cache(x);
+        self.textual_decoder_merged = self.textual_decoder_merged.with_graph_cache(x);
+        self.size_encoder = self.size_encoder.with_graph_cache(x);
+        self.size_decoder = self.size_decoder.with_graph_cache(x);
+        self.coord_encoder = self.coord_encoder.with_graph_cache(x);
+        self.coord_decoder = self.coord_decoder.with_graph_cache(x);
+        self.visual_projection = self.visual_projection.with_graph_cache(x);
+        self.textual_projection = self.textual_projection.with_graph_cache(x);
+        self
+    }
+
+    pub fn with_profiling_all(mut self, x: bool) -> Self {
+        self.visual = self.visual.with_profiling(x);
+        self.textual = self.textual.with_profiling(x);
+        self.model = self.model.with_profiling(x);
+        self.encoder = self.encoder.with_profiling(x);
+        self.decoder = self.decoder.with_profiling(x);
+        self.visual_encoder = self.visual_encoder.with_profiling(x);
+        self.textual_encoder = self.textual_encoder.with_profiling(x);
+        self.visual_decoder = self.visual_decoder.with_profiling(x);
+        self.textual_decoder = self.textual_decoder.with_profiling(x);
+        self.textual_decoder_merged = self.textual_decoder_merged.with_profiling(x);
+        self.size_encoder = self.size_encoder.with_profiling(x);
+        self.size_decoder = self.size_decoder.with_profiling(x);
+        self.coord_encoder = self.coord_encoder.with_profiling(x);
+        self.coord_decoder = self.coord_decoder.with_profiling(x);
+        self.visual_projection = self.visual_projection.with_profiling(x);
+        self.textual_projection = self.textual_projection.with_profiling(x);
+        self
+    }
+
     pub fn with_cpu_arena_allocator_all(mut self, x: bool) -> Self {
         self.visual = self.visual.with_cpu_arena_allocator(x);
         self.textual = self.textual.with_cpu_arena_allocator(x);
@@ -780,3 +1037,109 @@ impl_ort_config_methods!(Config, coord_decoder);
 impl_ort_config_methods!(Config, visual_projection);
 impl_ort_config_methods!(Config, textual_projection);
 impl_processor_config_methods!(Config, processor);
+
+#[cfg(test)]
+mod tests_config {
+    use super::Config;
+    use crate::{Device, Scale, ShapeBucket};
+
+    #[test]
+    fn test_roundtrip() {
+        let config = Config::yolo_detect()
+            .with_scale(Scale::S)
+            .with_version(8.into())
+            .with_model_device(Device::Cuda(1))
+            .with_shape_bucket(ShapeBucket::Multiple(32))
+            .with_class_confs(&[0.35]);
+        for format in ["toml", "json"] {
+            let s = config.to_string_with_format(format).unwrap();
+            let y = Config::from_str_with_format(&s, format).unwrap();
+            assert_eq!(y.name, "yolo");
+            assert_eq!(y.scale, Some(Scale::S));
+            assert_eq!(y.version, Some(8.into()));
+            assert_eq!(y.task, config.task);
+            assert_eq!(y.model.device, Device::Cuda(1));
+            assert_eq!(y.model.iiixs.len(), 2);
+            assert_eq!(y.processor.resize_filter, Some("CatmullRom"));
+            assert_eq!(y.processor.shape_bucket, Some(ShapeBucket::Multiple(32)));
+            assert_eq!(y.class_names.len(), 80);
+            assert_eq!(y.class_confs, [0.35]);
+        }
+
+        // every preset survives a roundtrip, with the resize filter in its canonical case
+        #[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+        {
+            let registry = crate::models::ModelRegistry::new();
+            for id in registry.ids() {
+                let config = registry.config(id).unwrap();
+                for format in ["toml", "json"] {
+                    let s = config.to_string_with_format(format).unwrap();
+                    let y = Config::from_str_with_format(&s, format)
+                        .unwrap_or_else(|err| panic!("{} ({}): {}", id, format, err));
+                    assert_eq!(y.name, config.name, "{}", id);
+                    assert_eq!(
+                        y.processor.resize_filter.map(str::to_lowercase),
+                        config.processor.resize_filter.map(str::to_lowercase),
+                        "{}",
+                        id
+                    );
+                    if let Some(x) = y.processor.resize_filter {
+                        assert!(
+                            crate::core::processor_config::RESIZE_FILTERS.contains(&x),
+                            "{}: {}",
+                            id,
+                            x
+                        );
+                    }
+                    let s = y.to_string_with_format(format).unwrap();
+                    let y = Config::from_str_with_format(&s, format).unwrap();
+                    assert_eq!(y.to_string_with_format(format).unwrap(), s, "{}", id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resize_filter_case() {
+        let y = Config::from_str_with_format("[processor]\nresize_filter = \"lanczos3\"", "toml")
+            .unwrap();
+        assert_eq!(y.processor.resize_filter, Some("Lanczos3"));
+        let y = Config::from_str_with_format(
+            r#"{"processor": {"resize_filter": "catmullrom"}}"#,
+            "json",
+        )
+        .unwrap();
+        assert_eq!(y.processor.resize_filter, Some("CatmullRom"));
+    }
+
+    #[test]
+    fn test_partial() {
+        let s = r#"
+            name = "db"
+            class_confs = [0.4]
+
+            [model]
+            file = "ppocr-v4-db.onnx"
+            device = "cpu:0"
+            dtype = "fp16"
+        "#;
+        let y = Config::from_str_with_format(s, "toml").unwrap();
+        assert_eq!(y.model.file, "ppocr-v4-db.onnx");
+        assert_eq!(y.model.dtype, crate::DType::Fp16);
+        assert_eq!(y.model.num_dry_run, 3);
+        assert_eq!(y.processor.resize_filter, Some("Bilinear"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| {
+            Config::from_str_with_format(s, "toml")
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err("[processor]\nresize_filter = \"Nearest\"").contains("processor.resize_filter"));
+        assert!(err("[model]\ndevice = \"npu:0\"").contains("model.device"));
+        assert!(err("[model]\nfiel = \"a.onnx\"").contains("fiel"));
+        assert!(err("class_confs = [0.5, 1.5]").contains("class_confs[1]"));
+    }
+}
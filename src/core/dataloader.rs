@@ -8,8 +8,12 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc;
 #[cfg(feature = "video")]
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "video")]
 use video_rs::{Decoder, Url};
 
+#[cfg(feature = "video")]
+use crate::FrameMeta;
 use crate::{Image, Location, MediaType};
 
 /// A structure designed to load and manage image, video, or stream data.
@@ -23,7 +27,7 @@ pub struct DataLoader {
     /// Batch size for iteration, determining how many files are processed at once.
     batch_size: usize,
 
-    /// Buffer size for the channel, used to manage the buffer between producer and consumer.
+    /// Maximum number of batches in flight, decoding or waiting for the consumer.
     bound: Option<usize>,
 
     /// Receiver for processed data.
@@ -40,15 +44,191 @@ pub struct DataLoader {
     #[cfg(feature = "video")]
     nf_skip: u64,
 
+    /// Time of the video to start from.
+    #[cfg(feature = "video")]
+    start: Option<Duration>,
+
+    /// Time of the video to stop at.
+    #[cfg(feature = "video")]
+    end: Option<Duration>,
+
+    /// Number of frames per second of video time to yield.
+    #[cfg(feature = "video")]
+    sample_fps: Option<f32>,
+
     /// Frame rate for video or stream.
     #[cfg(feature = "video")]
     frame_rate: f32,
 
+    /// Id set in the [`FrameMeta`] of video and stream frames.
+    stream_id: usize,
+
     /// Progress bar for displaying iteration progress.
     progress_bar: Option<ProgressBar>,
 
     /// Display progress bar or not.
     with_progress_bar: bool,
+
+    /// Seed of the shuffle applied to image paths, `None` to keep them sorted.
+    seed: Option<u64>,
+
+    /// Shard of the image paths to load, as `(index, count)`.
+    shard: Option<(usize, usize)>,
+
+    /// Number of images of the shard to skip, to resume an interrupted run.
+    start_at: usize,
+
+    /// Filter of images already processed, given their global index and path.
+    skip_processed: Option<SkipFn>,
+
+    /// In-memory source, used instead of `paths`.
+    memory: Option<Memory>,
+}
+
+type SkipFn = Box<dyn Fn(usize, &Path) -> bool + Send + Sync>;
+
+/// Entries handed to the producer thread, with their global index.
+type Items = Box<dyn Iterator<Item = (usize, Item)> + Send>;
+
+/// Extensions of the files listing one path or URL per line.
+const LIST_EXTENSIONS: &[&str] = &["txt", "csv"];
+
+/// An entry of the source, decoded into an [`Image`] by the producer thread.
+enum Item {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Image(Image),
+}
+
+impl Item {
+    /// Path given to the `skip_processed` filter, empty for in-memory entries.
+    fn path(&self) -> &Path {
+        match self {
+            Self::Path(x) => x,
+            _ => Path::new(""),
+        }
+    }
+
+    fn decode(self) -> Result<Image> {
+        match self {
+            Self::Path(x) => DataLoader::try_read_one(x),
+            Self::Bytes(x) => Ok(Image::from(image::load_from_memory(&x)?)
+                .with_media_type(MediaType::Image(Location::Local))),
+            Self::Image(x) => Ok(x),
+        }
+    }
+}
+
+/// In-memory sources of a [`DataLoader`].
+enum Memory {
+    Items(Vec<Item>),
+    Iter(Box<dyn Iterator<Item = Image> + Send>),
+}
+
+/// What to do with a decoded video frame.
+#[cfg(feature = "video")]
+#[derive(Debug, PartialEq)]
+enum Pick {
+    /// Yield it, with its index in the stream.
+    Keep(u64),
+    Skip,
+    Stop,
+}
+
+/// Picks the video frames to yield from their timestamps, in seconds.
+///
+/// Frames in `[start, end)` are kept every `nf_skip + 1` frames, then at most one per
+/// `period`. Timestamps are compared with half a frame of tolerance.
+#[cfg(feature = "video")]
+#[derive(Debug)]
+struct FrameSampler {
+    start: f64,
+    end: Option<f64>,
+    period: Option<f64>,
+    nf_skip: u64,
+    frame_rate: f64,
+    tolerance: f64,
+    /// Index of the first decoded frame, estimated from its timestamp after seeking.
+    offset: Option<u64>,
+    n_decoded: u64,
+    n_in_range: u64,
+    next: f64,
+}
+
+#[cfg(feature = "video")]
+impl FrameSampler {
+    fn new(
+        start: f64,
+        end: Option<f64>,
+        sample_fps: Option<f32>,
+        nf_skip: u64,
+        frame_rate: f32,
+    ) -> Self {
+        let tolerance = if frame_rate > 0. {
+            0.5 / frame_rate as f64
+        } else {
+            0.
+        };
+
+        Self {
+            start,
+            end,
+            period: sample_fps.map(|x| 1. / x as f64),
+            nf_skip,
+            frame_rate: frame_rate as f64,
+            tolerance,
+            offset: None,
+            n_decoded: 0,
+            n_in_range: 0,
+            next: start,
+        }
+    }
+
+    fn pick(&mut self, pts: f64) -> Pick {
+        let offset = match self.offset {
+            Some(x) => x,
+            None => {
+                let x = if self.start > 0. {
+                    (pts * self.frame_rate).round().max(0.) as u64
+                } else {
+                    0
+                };
+                *self.offset.insert(x)
+            }
+        };
+        self.n_decoded += 1;
+        let pts = pts + self.tolerance;
+        if pts < self.start {
+            return Pick::Skip;
+        }
+        if self.end.is_some_and(|x| pts >= x) {
+            return Pick::Stop;
+        }
+
+        self.n_in_range += 1;
+        if (self.n_in_range - 1) % (self.nf_skip + 1) != 0 {
+            return Pick::Skip;
+        }
+        if let Some(period) = self.period {
+            if pts < self.next {
+                return Pick::Skip;
+            }
+            while self.next <= pts {
+                self.next += period;
+            }
+        }
+
+        Pick::Keep(offset + self.n_decoded - 1)
+    }
+
+    /// Number of frames yielded from a range of `secs` seconds.
+    fn count(&self, secs: f64) -> u64 {
+        let mut fps = self.frame_rate / (self.nf_skip + 1) as f64;
+        if let Some(period) = self.period {
+            fps = fps.min(1. / period);
+        }
+        (secs.max(0.) * fps).ceil() as u64
+    }
 }
 
 impl Default for DataLoader {
@@ -60,10 +240,22 @@ impl Default for DataLoader {
             batch_size: 1,
             #[cfg(feature = "video")]
             nf_skip: 0,
+            #[cfg(feature = "video")]
+            start: None,
+            #[cfg(feature = "video")]
+            end: None,
+            #[cfg(feature = "video")]
+            sample_fps: None,
             bound: None,
             receiver: mpsc::sync_channel(0).1,
             progress_bar: None,
             with_progress_bar: false,
+            seed: None,
+            shard: None,
+            start_at: 0,
+            skip_processed: None,
+            memory: None,
+            stream_id: 0,
             #[cfg(feature = "video")]
             decoder: None,
             #[cfg(feature = "video")]
@@ -83,6 +275,9 @@ impl std::fmt::Debug for DataLoader {
             .field("media_type", &self.media_type)
             .field("bound", &self.bound)
             .field("progress_bar", &self.with_progress_bar)
+            .field("seed", &self.seed)
+            .field("shard", &self.shard)
+            .field("start_at", &self.start_at)
             .finish()
     }
 }
@@ -180,17 +375,151 @@ impl DataLoader {
         })
     }
 
+    /// Loads several image sources as one, in the given order.
+    ///
+    /// Each source can be anything [`DataLoader::new`] takes, but videos and streams
+    /// can only be loaded alone.
+    pub fn from_sources<S: AsRef<str>>(sources: &[S]) -> Result<Self> {
+        if let [source] = sources {
+            return Self::new(source.as_ref());
+        }
+        if sources.is_empty() {
+            anyhow::bail!("No source to load.");
+        }
+
+        let mut paths = VecDeque::new();
+        for source in sources.iter().map(|x| x.as_ref()) {
+            match Self::try_load_all(source)? {
+                (x, MediaType::Image(_)) => paths.extend(x.unwrap_or_default()),
+                (_, MediaType::Unknown) => {
+                    anyhow::bail!("Could not locate the source: {:?}", source)
+                }
+                (_, media_type) => anyhow::bail!(
+                    "Only image sources can be combined, but {:?} is {:?}.",
+                    source,
+                    media_type
+                ),
+            }
+        }
+        info!(
+            "Found {:?} x{} in {} sources",
+            MediaType::Image(Location::Local),
+            paths.len(),
+            sources.len()
+        );
+
+        Ok(Self {
+            nf: paths.len() as _,
+            paths: Some(paths),
+            media_type: MediaType::Image(Location::Local),
+            ..Default::default()
+        })
+    }
+
+    /// Loads images already in memory.
+    pub fn from_images(images: Vec<Image>) -> Self {
+        Self::from_memory(images.into_iter().map(Item::Image).collect())
+    }
+
+    /// Loads encoded images (PNG, JPEG, ...) from memory, decoding them like files.
+    pub fn from_bytes(bytes: Vec<Vec<u8>>) -> Self {
+        Self::from_memory(bytes.into_iter().map(Item::Bytes).collect())
+    }
+
+    /// Loads images from an iterator, pulled lazily by the producer thread.
+    ///
+    /// The progress bar knows the length only if the iterator reports an exact size.
+    pub fn from_iterator<I>(images: I) -> Self
+    where
+        I: IntoIterator<Item = Image>,
+        I::IntoIter: Send + 'static,
+    {
+        let images = images.into_iter();
+        let nf = match images.size_hint() {
+            (lo, Some(hi)) if lo == hi => hi as _,
+            _ => u64::MAX,
+        };
+
+        Self {
+            nf,
+            media_type: MediaType::Image(Location::Local),
+            memory: Some(Memory::Iter(Box::new(images))),
+            ..Default::default()
+        }
+    }
+
+    fn from_memory(items: Vec<Item>) -> Self {
+        Self {
+            nf: items.len() as _,
+            media_type: MediaType::Image(Location::Local),
+            memory: Some(Memory::Items(items)),
+            ..Default::default()
+        }
+    }
+
     pub fn build(mut self) -> Result<Self> {
-        let (sender, receiver) =
-            mpsc::sync_channel::<Vec<Image>>(self.bound.unwrap_or(self.batch_size * 10));
+        // images are decoded `window` batches at a time, which count towards `bound`
+        let bound = self.bound.unwrap_or(self.batch_size * 10).max(1);
+        let window = match self.media_type {
+            MediaType::Image(_) => rayon::current_num_threads()
+                .div_ceil(self.batch_size.max(1))
+                .clamp(1, bound),
+            _ => 0,
+        };
+        let (sender, receiver) = mpsc::sync_channel::<Vec<Image>>(bound - window);
         self.receiver = receiver;
         let batch_size = self.batch_size;
         #[cfg(feature = "video")]
-        let nf_skip = self.nf_skip;
-        let data = self.paths.take().unwrap_or_default();
+        let sampler = self.frame_sampler()?;
+        #[cfg(feature = "video")]
+        let stream_id = self.stream_id;
+        let lazy = matches!(self.memory, Some(Memory::Iter(_)));
+        if lazy {
+            self.nf = self.count_selected(self.nf);
+        }
+        let data = match self.memory.take() {
+            Some(Memory::Iter(x)) => self.select(x.map(Item::Image))?,
+            Some(Memory::Items(x)) => self.select(x.into_iter())?,
+            None => {
+                let paths = self.paths.take().unwrap_or_default();
+                self.select(paths.into_iter().map(Item::Path))?
+            }
+        };
+        // count what is left of listed sources after selection
+        let data: Items = match self.media_type {
+            MediaType::Image(_) if !lazy => {
+                let data: Vec<(usize, Item)> = data.collect();
+                self.nf = data.len() as _;
+                Box::new(data.into_iter())
+            }
+            _ => data,
+        };
         let media_type = self.media_type;
         #[cfg(feature = "video")]
-        let decoder = self.decoder.take();
+        let mut decoder = self.decoder.take();
+
+        // seek to the start of the range, then count the frames left in it
+        #[cfg(feature = "video")]
+        if let Some(decoder) = decoder.as_mut() {
+            if let (MediaType::Video(_), Some(start)) = (media_type, self.start) {
+                decoder.seek(start.as_millis() as i64)?;
+            }
+            if self.start.is_some() || self.end.is_some() || self.sample_fps.is_some() {
+                let end = self.end.map(|x| x.as_secs_f64()).or_else(|| {
+                    decoder
+                        .duration()
+                        .ok()
+                        .map(|x| x.as_secs_f64())
+                        .filter(|x| *x > 0.)
+                });
+                self.nf = match end {
+                    Some(end) => sampler.count(end - sampler.start),
+                    None => u64::MAX,
+                };
+            } else if self.nf != u64::MAX {
+                self.nf = self.nf.div_ceil(self.nf_skip + 1);
+            }
+        }
 
         // progress bar
         self.progress_bar = if self.with_progress_bar {
@@ -211,8 +540,11 @@ impl DataLoader {
                 sender,
                 data,
                 batch_size,
+                window,
+                #[cfg(feature = "video")]
+                sampler,
                 #[cfg(feature = "video")]
-                nf_skip,
+                stream_id,
                 media_type,
                 #[cfg(feature = "video")]
                 decoder,
@@ -222,11 +554,71 @@ impl DataLoader {
         Ok(self)
     }
 
+    /// Pairs the items with their global index, then shuffles, shards and skips them.
+    ///
+    /// Only shuffling needs the whole source; the rest is applied lazily.
+    fn select(&mut self, items: impl Iterator<Item = Item> + Send + 'static) -> Result<Items> {
+        let mut data: Items = Box::new(items.enumerate());
+        if let Some(seed) = self.seed {
+            let mut xs: Vec<(usize, Item)> = data.collect();
+            Self::shuffle(&mut xs, seed);
+            data = Box::new(xs.into_iter());
+        }
+        if let Some((index, count)) = self.shard {
+            if index >= count {
+                anyhow::bail!(
+                    "Invalid shard {} of {}. Expected index < count.",
+                    index,
+                    count
+                );
+            }
+            data = Box::new(data.skip(index).step_by(count));
+        }
+        data = Box::new(data.skip(self.start_at));
+        if let Some(f) = self.skip_processed.take() {
+            data = Box::new(data.filter(move |(i, x)| !f(*i, x.path())));
+        }
+
+        Ok(data)
+    }
+
+    /// Number of items left by [`DataLoader::select`] out of `n`, `u64::MAX` if unknown.
+    fn count_selected(&self, n: u64) -> u64 {
+        if n == u64::MAX || self.skip_processed.is_some() {
+            return u64::MAX;
+        }
+        let n = match self.shard {
+            Some((index, count)) => n.saturating_sub(index as u64).div_ceil(count.max(1) as u64),
+            None => n,
+        };
+
+        n.saturating_sub(self.start_at as u64)
+    }
+
+    /// Fisher-Yates with SplitMix64, so the order only depends on the seed and is
+    /// the same on every machine.
+    fn shuffle<T>(xs: &mut [T], seed: u64) {
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        for i in (1..xs.len()).rev() {
+            let j = (next() % (i as u64 + 1)) as usize;
+            xs.swap(i, j);
+        }
+    }
+
     fn producer_thread(
         sender: mpsc::SyncSender<Vec<Image>>,
-        mut data: VecDeque<PathBuf>,
+        mut data: Items,
         batch_size: usize,
-        #[cfg(feature = "video")] nf_skip: u64,
+        window: usize,
+        #[cfg(feature = "video")] mut sampler: FrameSampler,
+        #[cfg(feature = "video")] stream_id: usize,
         media_type: MediaType,
         #[cfg(feature = "video")] mut decoder: Option<video_rs::decode::Decoder>,
     ) {
@@ -234,62 +626,52 @@ impl DataLoader {
 
         match media_type {
             MediaType::Image(_) => {
-                if data.len() < 8000 {
-                    //  TODO: fast but memory inefficient
-                    crate::elapsed_dataloader!("batch_parallel_read", {
-                        data.par_iter()
-                            .filter_map(|path| {
-                                Some(crate::elapsed_dataloader!("single_image_read", {
-                                    Self::try_read_one(path)
-                                        .map_err(|e| warn!("Failed: {:?}, {}", path, e))
-                                        .ok()?
-                                        .with_media_type(media_type)
-                                }))
-                            })
-                            .collect::<Vec<Image>>()
-                            .chunks(batch_size)
-                            .for_each(|chunk| {
-                                if !chunk.is_empty() {
-                                    let _ = sender.send(chunk.to_vec());
-                                }
+                // decode in order on all cores, one window at a time
+                loop {
+                    let items: Vec<(usize, Item)> =
+                        data.by_ref().take(window * batch_size).collect();
+                    if items.is_empty() {
+                        break;
+                    }
+                    let decoded: Vec<Image> = crate::elapsed_dataloader!("batch_parallel_read", {
+                        items
+                            .into_par_iter()
+                            .filter_map(|(i, x)| {
+                                crate::elapsed_dataloader!("single_image_read", {
+                                    let path = x.path().to_path_buf();
+                                    x.decode()
+                                        .map_err(|e| warn!("Failed: #{} {:?}, {}", i, path, e))
+                                        .ok()
+                                        .map(|x| x.with_index(i))
+                                })
                             })
+                            .collect()
                     });
-                } else {
-                    // TODO: slow slow
-                    crate::elapsed_dataloader!("sequential_read", {
-                        while let Some(path) = data.pop_front() {
-                            match crate::elapsed_dataloader!("single_image_read", {
-                                Self::try_read_one(&path)
-                            }) {
-                                Err(_err) => {
-                                    continue;
-                                }
-                                Ok(img) => {
-                                    images.push(img.with_media_type(media_type));
-                                }
-                            }
-                            if images.len() == batch_size
-                                && sender.send(std::mem::take(&mut images)).is_err()
-                            {
-                                break;
-                            }
+                    images.extend(decoded);
+
+                    while images.len() >= batch_size {
+                        let rest = images.split_off(batch_size);
+                        if sender.send(std::mem::replace(&mut images, rest)).is_err() {
+                            info!("Receiver dropped, stopping production");
+                            return;
                         }
-                    });
+                    }
                 }
             }
             #[cfg(feature = "video")]
             MediaType::Video(_) | MediaType::Stream => {
                 if let Some(decoder) = decoder.as_mut() {
                     let (w, h) = decoder.size();
-                    let mut cnt = 0;
+                    let fps = decoder.frame_rate();
 
                     for frame in decoder.decode_iter() {
                         match frame {
                             Ok((ts, frame)) => {
-                                cnt += 1;
-                                if (cnt - 1) % (nf_skip + 1) != 0 {
-                                    continue;
-                                }
+                                let index = match sampler.pick(ts.as_secs_f64()) {
+                                    Pick::Keep(x) => x,
+                                    Pick::Skip => continue,
+                                    Pick::Stop => break,
+                                };
 
                                 let rgb8: image::RgbImage = match image::ImageBuffer::from_raw(
                                     w as _,
@@ -300,10 +682,17 @@ impl DataLoader {
                                     None => continue,
                                 };
 
+                                let frame = FrameMeta::default()
+                                    .with_index(index)
+                                    .with_pts(ts.as_secs_f64())
+                                    .with_stream_id(stream_id)
+                                    .with_fps(fps)
+                                    .with_decoded_at(SystemTime::now());
                                 images.push(
                                     Image::from(rgb8)
                                         .with_media_type(media_type)
-                                        .with_source(format!("{:?}", ts).into()),
+                                        .with_index(index as usize)
+                                        .with_frame(frame),
                                 );
 
                                 if images.len() == batch_size
@@ -446,6 +835,47 @@ impl DataLoader {
         Ok(paths)
     }
 
+    fn is_list_file(path: &Path) -> bool {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .is_some_and(|x| LIST_EXTENSIONS.contains(&x.to_lowercase().as_str()))
+    }
+
+    /// Reads one path or URL per line, taking the first column of `.csv` files.
+    ///
+    /// Blank lines and lines starting with `#` are skipped. The first row of a `.csv` file is
+    /// a header, and skipped, if its first column has no file extension, like `path,label`.
+    /// Relative paths are resolved against the folder of the list.
+    fn load_paths_from_list(path: &Path) -> Result<Vec<PathBuf>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read list file: {:?}. Error: {}", path, e))?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("csv"));
+        let root = path.parent().unwrap_or(Path::new(""));
+
+        Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let x = if is_csv {
+                    line.split(',').next()?.trim().trim_matches('"')
+                } else {
+                    line
+                };
+                if x.is_empty() || (is_csv && i == 0 && Path::new(x).extension().is_none()) {
+                    None
+                } else if MediaType::is_possible_remote(x) {
+                    Some(PathBuf::from(x))
+                } else {
+                    Some(root.join(x))
+                }
+            })
+            .collect())
+    }
+
     fn try_load_all(source: &str) -> Result<(Option<VecDeque<PathBuf>>, MediaType)> {
         // paths & media_type
         let is_source_remote = MediaType::is_possible_remote(source);
@@ -460,7 +890,15 @@ impl DataLoader {
         } else {
             // local
             log::debug!("DataLoader try to load source from local");
-            if source_path.is_file() {
+            if source_path.is_file() && Self::is_list_file(source_path) {
+                log::debug!("source is list file");
+                let paths = Self::load_paths_from_list(source_path)?;
+
+                (
+                    Some(VecDeque::from(paths)),
+                    MediaType::Image(Location::Local),
+                )
+            } else if source_path.is_file() {
                 log::debug!("source is file");
                 // image
                 (
@@ -528,16 +966,108 @@ impl DataLoader {
         self.nf_skip
     }
 
+    /// Starts the video at `t`, seeking to it instead of decoding from the beginning.
+    ///
+    /// For live streams, frames are skipped until their timestamp reaches `t`.
+    #[cfg(feature = "video")]
+    pub fn with_start(mut self, t: Duration) -> Self {
+        self.start = Some(t);
+        self
+    }
+
+    /// Stops the video before the first frame at or after `t`.
+    #[cfg(feature = "video")]
+    pub fn with_end(mut self, t: Duration) -> Self {
+        self.end = Some(t);
+        self
+    }
+
+    /// Yields at most `fps` frames per second of video time, e.g. `2.0` for one frame
+    /// every 0.5s. Applied after [`DataLoader::with_nf_skip`].
+    #[cfg(feature = "video")]
+    pub fn with_sample_fps(mut self, fps: f32) -> Self {
+        self.sample_fps = Some(fps);
+        self
+    }
+
+    #[cfg(feature = "video")]
+    fn frame_sampler(&self) -> Result<FrameSampler> {
+        let start = self.start.unwrap_or_default().as_secs_f64();
+        let end = self.end.map(|x| x.as_secs_f64());
+        if end.is_some_and(|x| x <= start) {
+            anyhow::bail!(
+                "Invalid time range: end {:?} is not after start {:?}.",
+                self.end,
+                self.start
+            );
+        }
+        if self.sample_fps.is_some_and(|x| x <= 0. || !x.is_finite()) {
+            anyhow::bail!(
+                "Invalid sample fps: {:?}. Expected a positive number.",
+                self.sample_fps
+            );
+        }
+
+        Ok(FrameSampler::new(
+            start,
+            end,
+            self.sample_fps,
+            self.nf_skip,
+            self.frame_rate,
+        ))
+    }
+
     #[cfg(feature = "video")]
     pub fn frame_rate(&self) -> f32 {
         self.frame_rate
     }
 
+    /// Sets the stream id of the frames, to tell several video sources apart.
+    pub fn with_stream_id(mut self, x: usize) -> Self {
+        self.stream_id = x;
+        self
+    }
+
+    pub fn stream_id(&self) -> usize {
+        self.stream_id
+    }
+
     pub fn with_progress_bar(mut self, x: bool) -> Self {
         self.with_progress_bar = x;
         self
     }
 
+    /// Shuffles the images with `seed`. The order is the same for the same seed and source.
+    ///
+    /// Sources built with [`DataLoader::from_iterator`] are collected first.
+    pub fn with_shuffle(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Only loads every `count`-th image starting at `index`, after shuffling.
+    ///
+    /// Runs with the same source, seed and `count` cover every image exactly once.
+    pub fn with_shard(mut self, index: usize, count: usize) -> Self {
+        self.shard = Some((index, count));
+        self
+    }
+
+    /// Skips the first `n` images of the shard, e.g. those yielded before a crash.
+    pub fn with_start_at(mut self, n: usize) -> Self {
+        self.start_at = n;
+        self
+    }
+
+    /// Skips the images for which `f(index, path)` returns `true`, e.g. those with saved results.
+    pub fn with_skip_processed<F>(mut self, f: F) -> Self
+    where
+        F: Fn(usize, &Path) -> bool + Send + Sync + 'static,
+    {
+        self.skip_processed = Some(Box::new(f));
+        self
+    }
+
     pub fn iter(&self) -> DataLoaderIter<'_> {
         DataLoaderIter {
             receiver: &self.receiver,
@@ -687,3 +1217,158 @@ impl<'a> IntoIterator for &'a DataLoader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests_dataloader {
+    use super::{DataLoader, Item};
+    #[cfg(feature = "video")]
+    use super::{FrameSampler, Pick};
+    use crate::Image;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+
+    fn widths(dl: DataLoader) -> Vec<u32> {
+        dl.build()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|x| x.width())
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_read_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 1..=7 {
+            image::RgbImage::new(i, 1)
+                .save(dir.path().join(format!("{}.png", i)))
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("4b.png"), b"not an image").unwrap();
+
+        let dl = DataLoader::new(dir.path().to_str().unwrap())
+            .unwrap()
+            .with_batch(2)
+            .with_bound(1)
+            .build()
+            .unwrap();
+        let batches: Vec<Vec<u32>> = dl
+            .into_iter()
+            .map(|xs| xs.iter().map(|x| x.width()).collect())
+            .collect();
+        assert_eq!(batches, vec![vec![1, 2], vec![3, 4], vec![5, 6], vec![7]]);
+    }
+
+    #[test]
+    fn test_select() {
+        let paths: VecDeque<PathBuf> = (0..10)
+            .map(|i| PathBuf::from(format!("{}.jpg", i)))
+            .collect();
+        let indices = |mut dl: DataLoader| -> Vec<usize> {
+            dl.select(paths.clone().into_iter().map(Item::Path))
+                .unwrap()
+                .map(|(i, _)| i)
+                .collect()
+        };
+
+        let shuffled = indices(DataLoader::default().with_shuffle(42));
+        assert_eq!(shuffled, indices(DataLoader::default().with_shuffle(42)));
+        assert_ne!(shuffled, (0..10).collect::<Vec<_>>());
+
+        let mut shards: Vec<usize> = (0..3)
+            .flat_map(|k| indices(DataLoader::default().with_shuffle(42).with_shard(k, 3)))
+            .collect();
+        shards.sort();
+        assert_eq!(shards, (0..10).collect::<Vec<_>>());
+
+        let shard = indices(DataLoader::default().with_shuffle(42).with_shard(1, 3));
+        let resumed = indices(
+            DataLoader::default()
+                .with_shuffle(42)
+                .with_shard(1, 3)
+                .with_start_at(1),
+        );
+        assert_eq!(resumed, shard[1..]);
+
+        let unprocessed = indices(DataLoader::default().with_skip_processed(|i, _| i % 2 == 0));
+        assert_eq!(unprocessed, vec![1, 3, 5, 7, 9]);
+
+        assert!(DataLoader::default()
+            .with_shard(3, 3)
+            .select(paths.clone().into_iter().map(Item::Path))
+            .is_err());
+    }
+
+    #[test]
+    fn test_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bytes = Vec::new();
+        for i in 1..=3 {
+            let path = dir.path().join(format!("{}.png", i));
+            image::RgbImage::new(i, 1).save(&path).unwrap();
+            bytes.push(std::fs::read(path).unwrap());
+        }
+        let list = dir.path().join("list.txt");
+        std::fs::write(&list, "# images\n3.png\n\n1.png\n").unwrap();
+        let csv = dir.path().join("list.csv");
+        std::fs::write(&csv, "path,label\n2.png,cat\n\"3.png\",dog\n").unwrap();
+
+        let list = list.to_str().unwrap();
+        let csv = csv.to_str().unwrap();
+        assert_eq!(widths(DataLoader::new(list).unwrap()), vec![3, 1]);
+        assert_eq!(widths(DataLoader::new(csv).unwrap()), vec![2, 3]);
+        assert_eq!(
+            widths(DataLoader::from_sources(&[list, dir.path().to_str().unwrap()]).unwrap()),
+            vec![3, 1, 1, 2, 3]
+        );
+        assert_eq!(
+            widths(DataLoader::from_bytes(bytes).with_batch(2)),
+            vec![1, 2, 3]
+        );
+
+        let images: Vec<Image> = (1..=4).map(|i| image::RgbImage::new(i, 1).into()).collect();
+        assert_eq!(
+            widths(DataLoader::from_images(images.clone()).with_start_at(1)),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            widths(DataLoader::from_iterator(images.clone()).with_shard(1, 2)),
+            vec![2, 4]
+        );
+
+        // lazy sources count what is left after selection
+        let nf = |dl: DataLoader| dl.build().unwrap().nf();
+        let dl = DataLoader::from_iterator(images.clone());
+        assert_eq!(nf(dl.with_shard(1, 2).with_start_at(1)), 1);
+        let dl = DataLoader::from_iterator(images.clone());
+        assert_eq!(nf(dl.with_skip_processed(|i, _| i == 0)), u64::MAX);
+        let dl = DataLoader::from_iterator(images.into_iter().filter(|x| x.width() > 1));
+        assert_eq!(nf(dl), u64::MAX);
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn test_frame_sampler() {
+        // 10 fps, 2 frames per second between 1s and 3s
+        let mut sampler = FrameSampler::new(1., Some(3.), Some(2.), 0, 10.);
+        let kept: Vec<u64> = (5..40)
+            .map(|i| (i, sampler.pick(i as f64 / 10.)))
+            .take_while(|(_, x)| *x != Pick::Stop)
+            .filter_map(|(i, x)| match x {
+                Pick::Keep(index) => {
+                    assert_eq!(index, i);
+                    Some(index)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(kept, vec![10, 15, 20, 25]);
+        assert_eq!(sampler.count(2.), 4);
+
+        let mut sampler = FrameSampler::new(0., None, None, 2, 10.);
+        let kept: Vec<Pick> = (0..7).map(|i| sampler.pick(i as f64 / 10.)).collect();
+        assert_eq!(kept[3], Pick::Keep(3));
+        assert_eq!(kept[4], Pick::Skip);
+        assert_eq!(sampler.count(0.7), 3);
+    }
+}
@@ -1,5 +1,10 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(into = "String")]
 /// Device types for model execution.
+///
+/// Serialized as the string accepted by [`std::str::FromStr`], e.g. `"cuda:0"`.
 pub enum Device {
     Cpu(usize),
     Cuda(usize),
@@ -103,6 +108,40 @@ impl std::str::FromStr for Device {
     }
 }
 
+impl<'de> Deserialize<'de> for Device {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<Device> for String {
+    fn from(x: Device) -> Self {
+        match x {
+            Device::Cpu(i) => format!("cpu:{}", i),
+            Device::Cuda(i) => format!("cuda:{}", i),
+            Device::TensorRt(i) => format!("tensorrt:{}", i),
+            Device::OpenVino(s) => format!("openvino:{}", s),
+            Device::DirectMl(i) => format!("directml:{}", i),
+            Device::Cann(i) => format!("cann:{}", i),
+            Device::Rocm(i) => format!("rocm:{}", i),
+            Device::Qnn(i) => format!("qnn:{}", i),
+            Device::MiGraphX(i) => format!("migraphx:{}", i),
+            Device::CoreMl => "coreml".to_string(),
+            Device::Xnnpack => "xnnpack".to_string(),
+            Device::RkNpu => "rknpu".to_string(),
+            Device::OneDnn => "onednn".to_string(),
+            Device::Acl => "acl".to_string(),
+            Device::NnApi => "nnapi".to_string(),
+            Device::ArmNn => "armnn".to_string(),
+            Device::Tvm => "tvm".to_string(),
+            Device::Vitis => "vitisai".to_string(),
+            Device::Azure => "azure".to_string(),
+        }
+    }
+}
+
 impl Device {
     pub fn id(&self) -> Option<usize> {
         match self {
@@ -1,4 +1,7 @@
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 /// Data type enumeration for tensor elements.
 pub enum DType {
     #[default]
@@ -28,6 +31,7 @@ pub enum DType {
     Fp4e2m1,
     Complex64,
     Complex128,
+    Bool,
 }
 
 impl std::str::FromStr for DType {
@@ -43,7 +47,7 @@ impl std::str::FromStr for DType {
             "u64" | "uint64" => Ok(Self::Uint64),
             "i4" | "int4" => Ok(Self::Int4),
             "i8" | "int8" => Ok(Self::Int8),
-            "i16" | "int=16" => Ok(Self::Int16),
+            "i16" | "int16" => Ok(Self::Int16),
             "i32" | "int32" => Ok(Self::Int32),
             "i64" | "int64" => Ok(Self::Int64),
             "f16" | "fp16" => Ok(Self::Fp16),
@@ -61,11 +65,26 @@ impl std::str::FromStr for DType {
             "f4e2m1" => Ok(Self::Fp4e2m1),
             "complex64" => Ok(Self::Complex64),
             "complex128" => Ok(Self::Complex128),
+            "bool" => Ok(Self::Bool),
             x => anyhow::bail!("Unsupported DType: {}", x),
         }
     }
 }
 
+impl TryFrom<String> for DType {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DType> for String {
+    fn from(x: DType) -> Self {
+        x.to_string()
+    }
+}
+
 impl std::fmt::Display for DType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let x = match self {
@@ -95,7 +114,51 @@ impl std::fmt::Display for DType {
             Self::Fp4e2m1 => "f4e2m1",
             Self::Complex64 => "complex64",
             Self::Complex128 => "complex128",
+            Self::Bool => "bool",
         };
         write!(f, "{}", x)
     }
 }
+
+impl DType {
+    /// Whether every value of `self` can be represented exactly in `to`.
+    ///
+    /// Only covers bool, integers and the IEEE-like floats; any other pair is lossy unless
+    /// both dtypes are the same.
+    pub fn is_lossless_cast_to(&self, to: &Self) -> bool {
+        // (is_float, is_signed, bits of precision)
+        fn precision(x: &DType) -> Option<(bool, bool, u32)> {
+            match x {
+                DType::Bool => Some((false, false, 1)),
+                DType::Uint8 => Some((false, false, 8)),
+                DType::Uint16 => Some((false, false, 16)),
+                DType::Uint32 => Some((false, false, 32)),
+                DType::Uint64 => Some((false, false, 64)),
+                DType::Int8 => Some((false, true, 7)),
+                DType::Int16 => Some((false, true, 15)),
+                DType::Int32 => Some((false, true, 31)),
+                DType::Int64 => Some((false, true, 63)),
+                DType::Bf16 => Some((true, true, 8)),
+                DType::Fp16 => Some((true, true, 11)),
+                DType::Fp32 => Some((true, true, 24)),
+                DType::Fp64 => Some((true, true, 53)),
+                _ => None,
+            }
+        }
+
+        if self == to {
+            return true;
+        }
+        match (precision(self), precision(to)) {
+            (Some((false, signed, bits)), Some((false, signed_to, bits_to))) => {
+                (signed_to || !signed) && bits <= bits_to
+            }
+            (Some((false, _, bits)), Some((true, _, bits_to))) => bits <= bits_to,
+            // bf16 has a wider exponent range than fp16
+            (Some((true, _, bits)), Some((true, _, bits_to))) => {
+                bits < bits_to && !matches!((self, to), (Self::Bf16, Self::Fp16))
+            }
+            _ => false,
+        }
+    }
+}
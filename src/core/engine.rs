@@ -5,16 +5,29 @@ use log::{debug, info, warn};
 use ndarray::{Array, IxDyn};
 use ort::{
     execution_providers::ExecutionProvider,
-    session::{builder::GraphOptimizationLevel, Session, SessionInputValue},
-    tensor::TensorElementType,
-    value::{DynValue, Value},
+    io_binding::IoBinding,
+    memory::Allocator,
+    session::{
+        builder::{GraphOptimizationLevel, PrepackedWeights},
+        run_options::OutputSelector,
+        RunOptions, Session, SessionInputValue,
+    },
+    tensor::{PrimitiveTensorElementType, TensorElementType},
+    value::{DynTensor, DynTensorValueType, DynValue, Value, ValueType},
 };
 use prost::Message;
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::Hasher,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
-    build_progress_bar, elapsed_global, human_bytes_binary, onnx, DType, Device, HardwareConfig,
-    Iiix, MinOptMax, ORTConfig, Ops, Xs, PROGRESS_BAR_STYLE_CYAN_2, PROGRESS_BAR_STYLE_FINISH, X,
+    build_progress_bar, elapsed_global, human_bytes_binary, onnx, pack_int4, pack_uint4,
+    unpack_int4, unpack_uint4, CancelToken, DType, Decryptor, Device, Fp8, HardwareConfig, Iiix,
+    MinOptMax, ModelBytes, ORTConfig, OnnxInspector, OnnxSummary, Ops, Perf, XAny, XAnys, Xs,
+    PROGRESS_BAR_STYLE_CYAN_2, PROGRESS_BAR_STYLE_FINISH, X,
 };
 
 impl From<TensorElementType> for DType {
@@ -40,6 +53,7 @@ impl From<TensorElementType> for DType {
             TensorElementType::Float8E5M2FNUZ => Self::Fp8e5m2fnuz,
             TensorElementType::Complex64 => Self::Complex64,
             TensorElementType::Complex128 => Self::Complex128,
+            TensorElementType::Bool => Self::Bool,
             _ => todo!(),
         }
     }
@@ -58,6 +72,96 @@ pub struct OrtTensorAttr {
     pub dimss: Vec<Vec<usize>>,
 }
 
+/// Mismatch between the given inputs and the model inputs, detected before the session runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputMismatch {
+    /// A model input was not provided.
+    Missing { name: String },
+    /// An input was provided that the model does not take.
+    Unknown { name: String, expected: Vec<String> },
+    /// The input dtype cannot be fed to the model input.
+    DType {
+        name: String,
+        expected: DType,
+        actual: DType,
+    },
+    /// The rank or a dimension of the input is out of the accepted range.
+    Shape {
+        name: String,
+        expected: Vec<MinOptMax>,
+        actual: Vec<usize>,
+    },
+}
+
+impl std::fmt::Display for InputMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Missing { name } => write!(f, "Input `{}` is missing", name),
+            Self::Unknown { name, expected } => write!(
+                f,
+                "Input `{}` is not a model input. Expected one of: {:?}",
+                name, expected
+            ),
+            Self::DType {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Input `{}` has dtype {}, which cannot be fed to the model input of dtype {}",
+                name, actual, expected
+            ),
+            Self::Shape {
+                name,
+                expected,
+                actual,
+            } => {
+                let expected: Vec<String> = expected
+                    .iter()
+                    .map(|x| {
+                        if x.min() == x.max() {
+                            x.opt().to_string()
+                        } else if x.max() == usize::MAX {
+                            format!("{}..", x.min())
+                        } else {
+                            format!("{}..={}", x.min(), x.max())
+                        }
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Input `{}` has dims {:?}, expected [{}]",
+                    name,
+                    actual,
+                    expected.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputMismatch {}
+
+impl InputMismatch {
+    /// Checks `actual` against the rank and the per-axis bounds in `expected`.
+    pub fn check_dims(name: &str, expected: &[MinOptMax], actual: &[usize]) -> Result<(), Self> {
+        let matched = expected.len() == actual.len()
+            && expected
+                .iter()
+                .zip(actual.iter())
+                .all(|(x, &y)| x.min() <= y && y <= x.max());
+        if matched {
+            Ok(())
+        } else {
+            Err(Self::Shape {
+                name: name.to_string(),
+                expected: expected.to_vec(),
+                actual: actual.to_vec(),
+            })
+        }
+    }
+}
+
 /// ONNX I/O structure containing input/output attributes and session.
 #[derive(Debug)]
 pub struct OnnxIo {
@@ -67,8 +171,74 @@ pub struct OnnxIo {
     pub outputs: OrtTensorAttr,
     /// ONNX Runtime session.
     pub session: Session,
-    /// ONNX model protocol buffer.
-    pub proto: onnx::ModelProto,
+    /// ONNX model protocol buffer, shared between pooled engines.
+    pub proto: Arc<onnx::ModelProto>,
+    /// Spec under which the ORT profile is merged into `Perf` once profiling ends.
+    pub(crate) profiling: Option<String>,
+}
+
+impl OnnxIo {
+    fn end_profiling(&mut self) -> Result<Option<PathBuf>> {
+        let spec = match self.profiling.take() {
+            Some(spec) => spec,
+            None => return Ok(None),
+        };
+        let p = PathBuf::from(self.session.end_profiling()?);
+        let n = Perf::merge_ort_profile(&spec, &p)?;
+        debug!("[{}] Merged {} profiling events: {}", spec, n, p.display());
+
+        Ok(Some(p))
+    }
+}
+
+impl Drop for OnnxIo {
+    fn drop(&mut self) {
+        if let Err(err) = self.end_profiling() {
+            warn!("Failed to end profiling: {}", err);
+        }
+    }
+}
+
+/// Preallocated I/O buffers reused across runs.
+///
+/// The inputs are refilled in place and bound again before every run, since binding copies
+/// them to the device. Outputs whose dims follow from the input dims are preallocated and
+/// bound once; the others are bound to the device and allocated by ORT on every run.
+#[derive(Debug)]
+pub struct OrtBinding {
+    /// Input buffers, refilled in place on every run.
+    pub inputs: Vec<DynValue>,
+    /// Input dimensions the buffers were allocated with.
+    pub dimss: Vec<Vec<usize>>,
+    /// Indices of the bound outputs.
+    pub outputs: Vec<usize>,
+    /// ONNX Runtime I/O binding of the session.
+    ///
+    /// `IoBinding` is not `Sync`. The mutex keeps `Engine` shareable by `&self` in the
+    /// models' parallel postprocessing; runs take `&mut self`, so it is never contended.
+    pub binding: Mutex<IoBinding>,
+}
+
+/// Prepacked weights container shared by the sessions of an [`crate::EnginePool`].
+#[derive(Debug, Clone)]
+pub struct SharedWeights(PrepackedWeights);
+
+// SAFETY: the container is an `Arc` around an ORT pointer that is only read by session
+// creation, and ORT guards the container's contents with its own mutex, so sessions may be
+// created from, and used on, any thread.
+unsafe impl Send for SharedWeights {}
+unsafe impl Sync for SharedWeights {}
+
+impl Default for SharedWeights {
+    fn default() -> Self {
+        Self(PrepackedWeights::new())
+    }
+}
+
+impl SharedWeights {
+    pub fn new() -> Self {
+        Self::default()
+    }
 }
 
 /// ONNX Runtime inference engine with configuration and session management.
@@ -76,10 +246,16 @@ pub struct OnnxIo {
 pub struct Engine {
     /// Model file path.
     pub file: String,
+    /// In-memory model, used instead of `file` when present.
+    pub bytes: Option<ModelBytes>,
+    /// Decryption hook applied to `file` before loading.
+    pub decryptor: Option<Decryptor>,
     /// Model specification string.
     pub spec: String,
     /// Execution device.
     pub device: Device,
+    /// Devices tried in order when building the session. The first one that works becomes `device`.
+    pub devices: Vec<Device>,
     #[args(inc)]
     pub iiixs: Vec<Iiix>,
     #[args(aka = "parameters")]
@@ -92,11 +268,25 @@ pub struct Engine {
     pub onnx: Option<OnnxIo>,
     /// Number of dry runs for warmup.
     pub num_dry_run: usize,
+    /// Reuse preallocated input buffers across runs.
+    pub io_binding: bool,
+    /// Buffers bound to the session when `io_binding` is enabled.
+    pub binding: Option<OrtBinding>,
+    /// Prepacked weights container shared with other sessions of the same model.
+    pub prepacked_weights: Option<SharedWeights>,
 
     // global
     pub graph_opt_level: Option<u8>,
     pub num_intra_threads: Option<usize>,
     pub num_inter_threads: Option<usize>,
+    /// Custom operator shared libraries registered on the session.
+    pub operator_libraries: Vec<String>,
+    /// Directory where optimized graphs are saved and loaded from on later runs.
+    pub graph_cache: Option<String>,
+    /// Enables ORT session profiling, merged into the `Perf` report when it ends.
+    pub profiling: bool,
+    /// Checked before every run, which fails with an [`crate::Interruption`] once it fires.
+    pub cancel_token: Option<CancelToken>,
 
     /// Hardware-specific configurations for all execution providers
     pub hardware: HardwareConfig,
@@ -106,10 +296,16 @@ impl Default for Engine {
     fn default() -> Self {
         Self {
             file: Default::default(),
+            bytes: None,
+            decryptor: None,
             device: Device::Cpu(0),
+            devices: vec![],
             spec: Default::default(),
             iiixs: Default::default(),
             num_dry_run: 3,
+            io_binding: false,
+            binding: None,
+            prepacked_weights: None,
             params: None,
             wbmems: None,
             inputs_minoptmax: vec![],
@@ -118,6 +314,10 @@ impl Default for Engine {
             graph_opt_level: None,
             num_intra_threads: None,
             num_inter_threads: None,
+            operator_libraries: vec![],
+            graph_cache: None,
+            profiling: false,
+            cancel_token: None,
             // hardware configurations
             hardware: HardwareConfig::new(),
         }
@@ -126,6 +326,11 @@ impl Default for Engine {
 
 impl Engine {
     pub fn try_from_config(config: &ORTConfig) -> Result<Self> {
+        Self::from_config(config).build()
+    }
+
+    /// Creates an engine from the config without loading the model.
+    pub(crate) fn from_config(config: &ORTConfig) -> Self {
         let hardware = HardwareConfig {
             cpu: crate::CpuConfig {
                 arena_allocator: config.hardware.cpu.arena_allocator,
@@ -170,25 +375,51 @@ impl Engine {
 
         Self {
             file: config.file.clone(),
+            bytes: config.bytes.clone(),
+            decryptor: config.decryptor.clone(),
             spec: config.spec.clone(),
             iiixs: config.iiixs.clone(),
             device: config.device,
+            devices: config.devices.clone(),
             num_dry_run: config.num_dry_run,
+            io_binding: config.io_binding,
             // global
             graph_opt_level: config.graph_opt_level,
             num_intra_threads: config.num_intra_threads,
             num_inter_threads: config.num_inter_threads,
+            operator_libraries: config.operator_libraries.clone(),
+            graph_cache: config.graph_cache.clone(),
+            profiling: config.profiling,
             // hardware configurations
             hardware,
             ..Default::default()
         }
-        .build()
     }
 
     pub fn build(mut self) -> Result<Self> {
+        let proto = self.load_proto()?;
+        self.build_with_proto(Arc::new(proto))
+    }
+
+    /// Loads the proto from memory, from the decrypted file, or from the file, in that order.
+    ///
+    /// A decrypted model is kept in `bytes` so the session is also committed from memory.
+    pub(crate) fn load_proto(&mut self) -> Result<onnx::ModelProto> {
+        if self.bytes.is_none() {
+            if let Some(decryptor) = &self.decryptor {
+                self.bytes = Some(decryptor.try_read(&self.file)?);
+            }
+        }
+        match &self.bytes {
+            Some(bytes) => Self::load_onnx_from_bytes(bytes),
+            None => Self::load_onnx(self.file()),
+        }
+    }
+
+    /// Builds the session from an already loaded proto, which may be shared with other engines.
+    pub(crate) fn build_with_proto(mut self, proto: Arc<onnx::ModelProto>) -> Result<Self> {
         let name = format!("[{}] ort_initialization", self.spec);
         elapsed_global!(&name, {
-            let proto = Self::load_onnx(self.file())?;
             let graph = match &proto.graph {
                 Some(graph) => graph,
                 None => {
@@ -204,17 +435,30 @@ impl Engine {
             let mut params: usize = 0;
             let mut wbmems: usize = 0;
             let mut initializer_names: HashSet<&str> = HashSet::new();
+            // external data is resolved relative to the model file
+            let model_dir = match &self.bytes {
+                Some(_) => None,
+                None => std::path::Path::new(&self.file)
+                    .parent()
+                    .map(|x| x.to_path_buf()),
+            };
             if !graph.initializer.is_empty() {
                 // from initializer
                 for tensor_proto in graph.initializer.iter() {
                     initializer_names.insert(&tensor_proto.name);
                     let param = tensor_proto.dims.iter().product::<i64>() as usize;
                     params += param;
-                    let param = Ops::make_divisible(param, byte_alignment);
-                    let n = Self::get_ort_dtype_from_proto_dtype_id(tensor_proto.data_type)
-                        .map(|x| x.byte_size(1))
-                        .unwrap_or_default();
-                    let wbmem = param * n;
+                    let wbmem = match Self::external_data_bytes(tensor_proto, model_dir.as_deref())?
+                    {
+                        Some(n) => Ops::make_divisible(n, byte_alignment),
+                        None => {
+                            let param = Ops::make_divisible(param, byte_alignment);
+                            let n = Self::get_ort_dtype_from_proto_dtype_id(tensor_proto.data_type)
+                                .map(|x| x.byte_size(1))
+                                .unwrap_or_default();
+                            param * n
+                        }
+                    };
                     wbmems += wbmem;
                 }
             } else {
@@ -239,13 +483,17 @@ impl Engine {
             self.wbmems = Some(wbmems);
 
             // inputs & outputs
-            let inputs = Self::io_from_onnx_value_info(&initializer_names, &graph.input)?;
-            let outputs = Self::io_from_onnx_value_info(&initializer_names, &graph.output)?;
+            let inputs = Self::io_from_onnx_value_info(&initializer_names, &graph.input, None)?;
             self.inputs_minoptmax = Self::build_ort_inputs(&inputs, self.iiixs())?;
 
             // session
             ort::init().commit()?;
             let session = self.build_session(&inputs)?;
+            let outputs = Self::io_from_onnx_value_info(
+                &initializer_names,
+                &graph.output,
+                Some(&session.outputs),
+            )?;
 
             // onnxio
             self.onnx = Some(OnnxIo {
@@ -253,7 +501,18 @@ impl Engine {
                 outputs,
                 proto,
                 session,
+                profiling: self.profiling.then(|| self.spec.clone()),
             });
+
+            // io binding
+            if self.io_binding {
+                let dimss: Vec<Vec<usize>> = self
+                    .inputs_minoptmax
+                    .iter()
+                    .map(|x| x.iter().map(|x| x.opt()).collect())
+                    .collect();
+                self.bind(&dimss)?;
+            }
         });
         self.dry_run()?;
         self.info();
@@ -313,37 +572,562 @@ impl Engine {
     }
 
     pub fn run(&mut self, xs: Xs) -> Result<Xs> {
+        self.run_selected(xs, None)
+    }
+
+    /// Like [`Engine::run`], but only fetches the outputs in `names`, in that order.
+    ///
+    /// ONNXRuntime does not copy out the other outputs, and they are never converted to `f32`.
+    pub fn run_with_outputs(&mut self, xs: Xs, names: &[&str]) -> Result<Xs> {
+        self.run_selected(xs, Some(names))
+    }
+
+    fn run_selected(&mut self, xs: Xs, names: Option<&[&str]>) -> Result<Xs> {
+        self.check_cancelled()?;
+        if self.io_binding {
+            return self.run_with_binding(xs, names);
+        }
+
         let mut ys = xs.derive();
-        if let Some(onnx) = &mut self.onnx {
-            // alignment
-            let xs_ = elapsed_global!(&format!("[{}] ort_preprocessing", self.spec), {
-                let mut xs_ = Vec::new();
-                for (dtype, x) in onnx.inputs.dtypes.iter().zip(xs.into_iter()) {
-                    xs_.push(Into::<SessionInputValue<'_>>::into(Self::preprocess(
-                        x, dtype,
-                    )?));
+        let dtypes = match &self.onnx {
+            Some(onnx) => onnx.inputs.dtypes.clone(),
+            None => anyhow::bail!("Failed to run with ONNXRuntime. No model info found."),
+        };
+
+        // alignment
+        let xs_ = elapsed_global!(&format!("[{}] ort_preprocessing", self.spec), {
+            let mut xs_ = Vec::new();
+            for (dtype, x) in dtypes.iter().zip(xs.into_iter()) {
+                xs_.push(Self::preprocess(x, dtype)?);
+            }
+
+            xs_
+        });
+
+        // run & extract
+        for (name, y) in self.run_values(xs_, names, Self::postprocess)? {
+            ys.push_kv(&name, X::from(y))?;
+        }
+
+        Ok(ys)
+    }
+
+    /// Runs with typed inputs and returns outputs in their native dtypes.
+    ///
+    /// Inputs whose dtype already matches the model input are passed to ONNXRuntime as-is,
+    /// without any cast or copy. Others are cast to the model input dtype.
+    pub fn run_any<T: Into<XAnys>>(&mut self, xs: T) -> Result<XAnys> {
+        self.run_any_selected(xs.into(), None)
+    }
+
+    /// Like [`Engine::run_any`], but only fetches the outputs in `names`, in that order.
+    pub fn run_any_with_outputs<T: Into<XAnys>>(&mut self, xs: T, names: &[&str]) -> Result<XAnys> {
+        self.run_any_selected(xs.into(), Some(names))
+    }
+
+    fn run_any_selected(&mut self, xs: XAnys, names: Option<&[&str]>) -> Result<XAnys> {
+        self.check_cancelled()?;
+        let dtypes = match &self.onnx {
+            Some(onnx) => onnx.inputs.dtypes.clone(),
+            None => anyhow::bail!("Failed to run with ONNXRuntime. No model info found."),
+        };
+
+        // alignment
+        let xs_ = elapsed_global!(&format!("[{}] ort_preprocessing", self.spec), {
+            let mut xs_ = Vec::new();
+            for (dtype, x) in dtypes.iter().zip(xs.into_values()) {
+                xs_.push(Self::preprocess_any(x, dtype)?);
+            }
+
+            xs_
+        });
+
+        // run & extract
+        let mut ys = XAnys::default();
+        for (name, y) in self.run_values(xs_, names, Self::postprocess_any)? {
+            ys.push_kv(&name, y)?;
+        }
+
+        Ok(ys)
+    }
+
+    /// Runs with inputs keyed by the model input names and returns outputs in their native dtypes.
+    ///
+    /// Inputs are validated before the session runs: every model input must be provided once,
+    /// with a dtype that casts losslessly to the model dtype, the same rank, and dimensions
+    /// within the `MinOptMax` bounds. Dynamic dimensions not configured with `iiixs` are unbounded.
+    /// Violations are reported as [`InputMismatch`].
+    pub fn run_named<T: Into<XAnys>>(&mut self, xs: T) -> Result<XAnys> {
+        let xs = self.check_named(xs.into())?;
+        self.run_any_selected(xs, None)
+    }
+
+    /// Validates named inputs and returns them in the model input order.
+    fn check_named(&self, xs: XAnys) -> Result<XAnys> {
+        let onnx = match &self.onnx {
+            Some(onnx) => onnx,
+            None => anyhow::bail!("Failed to run with ONNXRuntime. No model info found."),
+        };
+        let mut xs: std::collections::HashMap<String, XAny> = xs.into_pairs().into_iter().collect();
+        if let Some(name) = xs.keys().find(|x| !onnx.inputs.names.contains(x)) {
+            return Err(InputMismatch::Unknown {
+                name: name.to_string(),
+                expected: onnx.inputs.names.clone(),
+            }
+            .into());
+        }
+
+        let mut ys = XAnys::default();
+        for (i, (name, dtype)) in onnx
+            .inputs
+            .names
+            .iter()
+            .zip(onnx.inputs.dtypes.iter())
+            .enumerate()
+        {
+            let x = match xs.remove(name) {
+                Some(x) => x,
+                None => return Err(InputMismatch::Missing { name: name.clone() }.into()),
+            };
+            // low-bit inputs are packed from fp32, which XAny cannot hold otherwise
+            let lossless = if Self::is_low_bit(dtype) {
+                x.dtype() == DType::Fp32
+            } else {
+                x.dtype().is_lossless_cast_to(&DType::from(*dtype))
+            };
+            if !Self::is_supported_dtype(dtype) || !lossless {
+                return Err(InputMismatch::DType {
+                    name: name.clone(),
+                    expected: DType::from(*dtype),
+                    actual: x.dtype(),
                 }
+                .into());
+            }
+            InputMismatch::check_dims(name, &self.input_bounds(i), x.dims())?;
+            ys.push_kv(name, x)?;
+        }
 
-                xs_
-            });
+        Ok(ys)
+    }
 
-            // run
-            let outputs = elapsed_global!(
-                &format!("[{}] ort_inference", self.spec),
-                onnx.session.run(&xs_[..])?
+    /// Per-axis bounds of the `i`-th input: static dims, or dynamic dims configured with
+    /// `iiixs`. Dynamic dims left unconfigured are unbounded.
+    fn input_bounds(&self, i: usize) -> Vec<MinOptMax> {
+        let dims = match self.onnx.as_ref().and_then(|x| x.inputs.dimss.get(i)) {
+            Some(dims) => dims,
+            None => return self.inputs_minoptmax[i].clone(),
+        };
+        dims.iter()
+            .zip(self.inputs_minoptmax[i].iter())
+            .enumerate()
+            .map(|(ii, (&d, x))| {
+                // the batch size of every input follows the first one
+                let configured = d != 0
+                    || self.iiixs.iter().any(|iiix| {
+                        (iiix.i == i && iiix.ii == ii) || (ii == 0 && iiix.i == 0 && iiix.ii == 0)
+                    });
+                if configured {
+                    x.clone()
+                } else {
+                    MinOptMax::from((0, x.opt(), usize::MAX))
+                }
+            })
+            .collect()
+    }
+
+    fn is_supported_dtype(dtype: &TensorElementType) -> bool {
+        matches!(
+            dtype,
+            TensorElementType::Float32
+                | TensorElementType::Float16
+                | TensorElementType::Bfloat16
+                | TensorElementType::Float64
+                | TensorElementType::Int64
+                | TensorElementType::Int32
+                | TensorElementType::Int16
+                | TensorElementType::Int8
+                | TensorElementType::Uint64
+                | TensorElementType::Uint32
+                | TensorElementType::Uint16
+                | TensorElementType::Uint8
+                | TensorElementType::Bool
+        ) || Self::is_low_bit(dtype)
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if let Some(x) = self.cancel_token.as_ref().and_then(|x| x.interrupted()) {
+            return Err(anyhow::Error::new(x).context(format!("[{}] Run {}", self.spec, x)));
+        }
+        Ok(())
+    }
+
+    fn run_values<T>(
+        &mut self,
+        xs: Vec<DynValue>,
+        names: Option<&[&str]>,
+        extract: impl Fn(&DynValue, &TensorElementType) -> Result<T>,
+    ) -> Result<Vec<(String, T)>> {
+        let onnx = match &mut self.onnx {
+            Some(onnx) => onnx,
+            None => anyhow::bail!("Failed to run with ONNXRuntime. No model info found."),
+        };
+        let selected = Self::select_outputs(&onnx.outputs, names)?;
+        let xs: Vec<SessionInputValue<'_>> = xs.into_iter().map(Into::into).collect();
+
+        // run
+        let options = match names {
+            Some(_) => {
+                let selector = selected
+                    .iter()
+                    .fold(OutputSelector::no_default(), |selector, &i| {
+                        selector.with(onnx.outputs.names[i].as_str())
+                    });
+                Some(RunOptions::new()?.with_outputs(selector))
+            }
+            None => None,
+        };
+        let outputs = elapsed_global!(
+            &format!("[{}] ort_inference", self.spec),
+            match &options {
+                Some(options) => onnx.session.run_with_options(&xs[..], options)?,
+                None => onnx.session.run(&xs[..])?,
+            }
+        );
+
+        // extract
+        elapsed_global!(&format!("[{}] ort_postprocessing", self.spec), {
+            let mut ys = Vec::with_capacity(selected.len());
+            for i in selected {
+                let name = &onnx.outputs.names[i];
+                let y = extract(&outputs[name.as_str()], &onnx.outputs.dtypes[i])?;
+                ys.push((name.to_owned(), y));
+            }
+
+            Ok(ys)
+        })
+    }
+
+    /// Indices of the outputs to fetch, all of them if `names` is `None`.
+    fn select_outputs(outputs: &OrtTensorAttr, names: Option<&[&str]>) -> Result<Vec<usize>> {
+        match names {
+            None => Ok((0..outputs.names.len()).collect()),
+            Some(names) => names
+                .iter()
+                .map(|&name| {
+                    outputs.names.iter().position(|x| x == name).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Failed to select output `{}`. Expected one of {:?}",
+                            name,
+                            outputs.names
+                        )
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Runs with the preallocated buffers, filling the inputs in place and binding them again.
+    ///
+    /// Buffers are only reallocated when the input dimensions differ from the last run, and
+    /// outputs are only bound again when the selected outputs change.
+    fn run_with_binding(&mut self, xs: Xs, names: Option<&[&str]>) -> Result<Xs> {
+        let mut ys = xs.derive();
+        let dimss: Vec<Vec<usize>> = xs.into_iter().map(|x| x.dims().to_vec()).collect();
+        let n = match &self.onnx {
+            Some(onnx) => onnx.inputs.names.len(),
+            None => anyhow::bail!("Failed to run with ONNXRuntime. No model info found."),
+        };
+        if dimss.len() != n {
+            anyhow::bail!(
+                "Failed to run with I/O binding. Expected {} inputs, got {}.",
+                n,
+                dimss.len()
             );
+        }
+        if self.binding.as_ref().map(|x| &x.dimss) != Some(&dimss) {
+            self.bind(&dimss)?;
+        }
+        self.bind_outputs(names)?;
+        let (onnx, binding) = match (&mut self.onnx, &mut self.binding) {
+            (Some(onnx), Some(binding)) => (onnx, binding),
+            _ => anyhow::bail!("Failed to run with I/O binding. No bound buffers found."),
+        };
 
-            // extract
-            elapsed_global!(&format!("[{}] ort_postprocessing", self.spec), {
-                for (dtype, name) in onnx.outputs.dtypes.iter().zip(onnx.outputs.names.iter()) {
-                    let y = Self::postprocess(&outputs[name.as_str()], dtype)?;
-                    ys.push_kv(name.as_str(), X::from(y))?;
-                }
+        let io_binding = binding
+            .binding
+            .get_mut()
+            .map_err(|e| anyhow::anyhow!("Mutex lock error: {e}"))?;
+
+        // fill
+        elapsed_global!(&format!("[{}] ort_preprocessing", self.spec), {
+            for ((y, x), dtype) in binding
+                .inputs
+                .iter_mut()
+                .zip(xs.into_iter())
+                .zip(onnx.inputs.dtypes.iter())
+            {
+                Self::fill(y, x, dtype)?;
+            }
+
+            // `bind_input` copies to the device, so the filled buffers are bound again
+            for (name, x) in onnx.inputs.names.iter().zip(binding.inputs.iter()) {
+                io_binding.bind_input(name, x)?;
+            }
+        });
+
+        // run
+        let outputs = elapsed_global!(
+            &format!("[{}] ort_inference", self.spec),
+            onnx.session.run_binding(io_binding)?
+        );
+
+        // extract: only the selected outputs are bound
+        elapsed_global!(&format!("[{}] ort_postprocessing", self.spec), {
+            for &i in binding.outputs.iter() {
+                let name = &onnx.outputs.names[i];
+                let y = Self::postprocess(&outputs[name.as_str()], &onnx.outputs.dtypes[i])?;
+                ys.push_kv(name, X::from(y))?;
+            }
+        });
+
+        Ok(ys)
+    }
+
+    /// (Re)allocates the input buffers for the given input dimensions.
+    ///
+    /// Outputs are bound afterwards by [`Engine::bind_outputs`].
+    fn bind(&mut self, dimss: &[Vec<usize>]) -> Result<()> {
+        let onnx = match &self.onnx {
+            Some(onnx) => onnx,
+            None => anyhow::bail!("Failed to bind with ONNXRuntime. No model info found."),
+        };
+        elapsed_global!(&format!("[{}] ort_binding", self.spec), {
+            self.binding = None;
+            let binding = onnx.session.create_binding()?;
+            let inputs = dimss
+                .iter()
+                .zip(onnx.inputs.dtypes.iter())
+                .map(|(dims, dtype)| Self::alloc(dims, dtype))
+                .collect::<Result<Vec<_>>>()?;
+            debug!(
+                "[{}] I/O buffers allocated with input dims: {:?}",
+                self.spec, dimss
+            );
+            self.binding = Some(OrtBinding {
+                inputs,
+                dimss: dimss.to_vec(),
+                outputs: vec![],
+                binding: Mutex::new(binding),
             });
+        });
 
-            Ok(ys)
+        Ok(())
+    }
+
+    /// Binds the outputs in `names`, all of them if `None`, unless they are already bound.
+    ///
+    /// Outputs whose dims are resolved from the bound input dims get a preallocated buffer,
+    /// the others are bound to the device and allocated by ORT on every run.
+    fn bind_outputs(&mut self, names: Option<&[&str]>) -> Result<()> {
+        let (onnx, binding) = match (&self.onnx, &mut self.binding) {
+            (Some(onnx), Some(binding)) => (onnx, binding),
+            _ => anyhow::bail!("Failed to bind outputs. No bound buffers found."),
+        };
+        let selected = Self::select_outputs(&onnx.outputs, names)?;
+        if binding.outputs == selected {
+            return Ok(());
+        }
+
+        elapsed_global!(&format!("[{}] ort_binding", self.spec), {
+            let io_binding = binding
+                .binding
+                .get_mut()
+                .map_err(|e| anyhow::anyhow!("Mutex lock error: {e}"))?;
+            io_binding.clear_outputs();
+            let dimss = Self::resolve_output_dims(&onnx.session, &binding.dimss);
+            let memory_info = onnx.session.allocator().memory_info();
+            for &i in selected.iter() {
+                let name = &onnx.outputs.names[i];
+                match &dimss[i] {
+                    Some(dims) => {
+                        io_binding.bind_output(name, Self::alloc(dims, &onnx.outputs.dtypes[i])?)?
+                    }
+                    None => io_binding.bind_output_to_device(name, &memory_info)?,
+                }
+            }
+            debug!(
+                "[{}] Outputs bound with dims: {:?}",
+                self.spec,
+                selected.iter().map(|&i| &dimss[i]).collect::<Vec<_>>()
+            );
+            binding.outputs = selected;
+        });
+
+        Ok(())
+    }
+
+    /// Output dims given the input dims, taken from the static dims and from the dynamic ones
+    /// named like an input dim. `None` for outputs with any other dynamic dim, as their shapes
+    /// depend on the data.
+    fn resolve_output_dims(session: &Session, dimss: &[Vec<usize>]) -> Vec<Option<Vec<usize>>> {
+        let resolve = |d: i64, symbol: &String| -> Option<usize> {
+            if d >= 0 {
+                return Some(d as usize);
+            }
+            if symbol.is_empty() {
+                return None;
+            }
+            session
+                .inputs
+                .iter()
+                .zip(dimss.iter())
+                .find_map(|(input, dims)| match &input.input_type {
+                    ValueType::Tensor {
+                        dimension_symbols, ..
+                    } => dimension_symbols
+                        .iter()
+                        .position(|x| x == symbol)
+                        .and_then(|ii| dims.get(ii).copied()),
+                    _ => None,
+                })
+        };
+
+        session
+            .outputs
+            .iter()
+            .map(|output| match &output.output_type {
+                ValueType::Tensor {
+                    shape,
+                    dimension_symbols,
+                    ..
+                } => shape
+                    .iter()
+                    .zip(dimension_symbols.iter())
+                    .map(|(&d, symbol)| resolve(d, symbol))
+                    .collect(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn alloc(dims: &[usize], dtype: &TensorElementType) -> Result<DynValue> {
+        fn _alloc<T>(dims: &[usize]) -> Result<DynValue>
+        where
+            T: PrimitiveTensorElementType + Default + Clone + std::fmt::Debug + 'static,
+        {
+            Ok(Value::from_array(Array::<T, IxDyn>::from_elem(dims, T::default()))?.into_dyn())
+        }
+        match dtype {
+            TensorElementType::Float32 => _alloc::<f32>(dims),
+            TensorElementType::Float16 => _alloc::<f16>(dims),
+            TensorElementType::Bfloat16 => _alloc::<bf16>(dims),
+            TensorElementType::Float64 => _alloc::<f64>(dims),
+            TensorElementType::Int64 => _alloc::<i64>(dims),
+            TensorElementType::Int32 => _alloc::<i32>(dims),
+            TensorElementType::Int16 => _alloc::<i16>(dims),
+            TensorElementType::Int8 => _alloc::<i8>(dims),
+            TensorElementType::Uint64 => _alloc::<u64>(dims),
+            TensorElementType::Uint32 => _alloc::<u32>(dims),
+            TensorElementType::Uint16 => _alloc::<u16>(dims),
+            TensorElementType::Uint8 => _alloc::<u8>(dims),
+            TensorElementType::Bool => _alloc::<bool>(dims),
+            x if Self::is_low_bit(x) => {
+                let shape: Vec<i64> = dims.iter().map(|&x| x as i64).collect();
+                Ok(DynTensor::new(&Allocator::default(), *dtype, shape)?.into_dyn())
+            }
+            _ => Err(anyhow::anyhow!("Unsupported ort tensor type: {:?}", dtype)),
+        }
+    }
+
+    fn fill(y: &mut DynValue, x: &X, dtype: &TensorElementType) -> Result<()> {
+        fn _fill<T>(y: &mut DynValue, x: &X, map_fn: impl Fn(f32) -> T) -> Result<()>
+        where
+            T: PrimitiveTensorElementType + Clone + std::fmt::Debug + 'static,
+        {
+            let mut y = y.try_extract_array_mut::<T>()?;
+            y.zip_mut_with(&x.0, |y, &x| *y = map_fn(x));
+            Ok(())
+        }
+        match dtype {
+            TensorElementType::Float32 => _fill::<f32>(y, x, |x| x),
+            TensorElementType::Float16 => _fill::<f16>(y, x, f16::from_f32),
+            TensorElementType::Bfloat16 => _fill::<bf16>(y, x, bf16::from_f32),
+            TensorElementType::Float64 => _fill::<f64>(y, x, |x| x as f64),
+            TensorElementType::Int64 => _fill::<i64>(y, x, |x| x as i64),
+            TensorElementType::Int32 => _fill::<i32>(y, x, |x| x as i32),
+            TensorElementType::Int16 => _fill::<i16>(y, x, |x| x as i16),
+            TensorElementType::Int8 => _fill::<i8>(y, x, |x| x as i8),
+            TensorElementType::Uint64 => _fill::<u64>(y, x, |x| x as u64),
+            TensorElementType::Uint32 => _fill::<u32>(y, x, |x| x as u32),
+            TensorElementType::Uint16 => _fill::<u16>(y, x, |x| x as u16),
+            TensorElementType::Uint8 => _fill::<u8>(y, x, |x| x as u8),
+            TensorElementType::Bool => _fill::<bool>(y, x, |x| x != 0.),
+            x_ if Self::is_low_bit(x_) => {
+                let bytes = Self::encode_low_bit(x, dtype)?;
+                let mut y = y.downcast_mut::<DynTensorValueType>()?;
+                let ptr = y.data_ptr_mut() as *mut u8;
+                // SAFETY: ORT allocated the tensor with the packed size of `x.dims()` for `dtype`
+                unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+                Ok(())
+            }
+            _ => Err(anyhow::anyhow!("Unsupported ort tensor type: {:?}", dtype)),
+        }
+    }
+
+    fn preprocess_any(x: XAny, dtype: &TensorElementType) -> Result<DynValue> {
+        // low-bit tensors are packed from f32
+        if Self::is_low_bit(dtype) {
+            return Self::preprocess(&x.into_x(), dtype);
+        }
+        let x = if x.dtype() != DType::from(*dtype) {
+            debug!("Casting input from {} to {:?}", x.dtype(), dtype);
+            x.cast(DType::from(*dtype))?
         } else {
-            anyhow::bail!("Failed to run with ONNXRuntime. No model info found.");
+            x
+        };
+
+        let x = match x {
+            XAny::Fp16(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Bf16(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Fp32(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Fp64(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Int8(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Int16(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Int32(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Int64(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Uint8(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Uint16(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Uint32(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Uint64(x) => Value::from_array(x)?.into_dyn(),
+            XAny::Bool(x) => Value::from_array(x)?.into_dyn(),
+        };
+        Ok(x)
+    }
+
+    fn postprocess_any(x: &DynValue, dtype: &TensorElementType) -> Result<XAny> {
+        fn _extract<T>(x: &DynValue) -> Result<XAny>
+        where
+            T: Clone + 'static + ort::tensor::PrimitiveTensorElementType,
+            Array<T, IxDyn>: Into<XAny>,
+        {
+            Ok(x.try_extract_array::<T>()?.into_owned().into())
+        }
+        match dtype {
+            TensorElementType::Float32 => _extract::<f32>(x),
+            TensorElementType::Float16 => _extract::<f16>(x),
+            TensorElementType::Bfloat16 => _extract::<bf16>(x),
+            TensorElementType::Float64 => _extract::<f64>(x),
+            TensorElementType::Int64 => _extract::<i64>(x),
+            TensorElementType::Int32 => _extract::<i32>(x),
+            TensorElementType::Int16 => _extract::<i16>(x),
+            TensorElementType::Int8 => _extract::<i8>(x),
+            TensorElementType::Uint64 => _extract::<u64>(x),
+            TensorElementType::Uint32 => _extract::<u32>(x),
+            TensorElementType::Uint16 => _extract::<u16>(x),
+            TensorElementType::Uint8 => _extract::<u8>(x),
+            TensorElementType::Bool => _extract::<bool>(x),
+            x_ if Self::is_low_bit(x_) => Self::decode_low_bit(x, dtype),
+            _ => Err(anyhow::anyhow!("Unsupported ort tensor type: {:?}", dtype)),
         }
     }
 
@@ -363,7 +1147,12 @@ impl Engine {
             TensorElementType::Uint32 => Value::from_array(x.mapv(|x_| x_ as u32))?.into_dyn(),
             TensorElementType::Uint64 => Value::from_array(x.mapv(|x_| x_ as u64))?.into_dyn(),
             TensorElementType::Bool => Value::from_array(x.mapv(|x_| x_ != 0.))?.into_dyn(),
-            _ => unimplemented!(),
+            x_ if Self::is_low_bit(x_) => {
+                let mut y = Self::alloc(x.dims(), dtype)?;
+                Self::fill(&mut y, x, dtype)?;
+                y
+            }
+            _ => anyhow::bail!("Unsupported ort tensor type: {:?}", dtype),
         };
         Ok(x)
     }
@@ -395,14 +1184,113 @@ impl Engine {
             TensorElementType::Uint16 => _extract_and_convert::<u16>(x, |x| x as f32),
             TensorElementType::Uint8 => _extract_and_convert::<u8>(x, |x| x as f32),
             TensorElementType::Bool => _extract_and_convert::<bool>(x, |x| x as u8 as f32),
+            x_ if Self::is_low_bit(x_) => Self::decode_low_bit(x, dtype)?.into_x().0,
             _ => return Err(anyhow::anyhow!("Unsupported ort tensor type: {:?}", dtype)),
         };
 
         Ok(x)
     }
 
-    #[allow(unused_variables)]
+    fn fp8(dtype: &TensorElementType) -> Option<Fp8> {
+        match dtype {
+            TensorElementType::Float8E4M3FN => Some(Fp8::E4M3FN),
+            TensorElementType::Float8E4M3FNUZ => Some(Fp8::E4M3FNUZ),
+            TensorElementType::Float8E5M2 => Some(Fp8::E5M2),
+            TensorElementType::Float8E5M2FNUZ => Some(Fp8::E5M2FNUZ),
+            _ => None,
+        }
+    }
+
+    /// FP8 and 4-bit types have no Rust counterpart in ort and are handled as raw bytes.
+    fn is_low_bit(dtype: &TensorElementType) -> bool {
+        Self::fp8(dtype).is_some()
+            || matches!(dtype, TensorElementType::Int4 | TensorElementType::Uint4)
+    }
+
+    /// Encodes `x` in the byte layout ORT uses for `dtype`: one byte per FP8 element,
+    /// two 4-bit elements per byte with the first one in the low nibble.
+    fn encode_low_bit(x: &X, dtype: &TensorElementType) -> Result<Vec<u8>> {
+        let bytes = match (Self::fp8(dtype), dtype) {
+            (Some(fp8), _) => x.iter().map(|&x| fp8.from_f32(x)).collect(),
+            (None, TensorElementType::Int4) => {
+                let xs: Vec<i8> = x.iter().map(|x| x.round().clamp(-8., 7.) as i8).collect();
+                pack_int4(&xs)
+            }
+            (None, TensorElementType::Uint4) => {
+                let xs: Vec<u8> = x.iter().map(|x| x.round().clamp(0., 15.) as u8).collect();
+                pack_uint4(&xs)
+            }
+            _ => anyhow::bail!("Unsupported ort tensor type: {:?}", dtype),
+        };
+
+        Ok(bytes)
+    }
+
+    /// Decodes an FP8 or 4-bit tensor. FP8 is widened to `f32`, 4-bit to 8-bit integers.
+    fn decode_low_bit(x: &DynValue, dtype: &TensorElementType) -> Result<XAny> {
+        let dims: Vec<usize> = match x.dtype() {
+            ValueType::Tensor { shape, .. } => shape.iter().map(|&x| x.max(0) as usize).collect(),
+            _ => anyhow::bail!("Failed to decode ort output. Not a tensor: {:?}", x.dtype()),
+        };
+        let n = dims.iter().product::<usize>();
+        let n_bytes = if Self::fp8(dtype).is_some() {
+            n
+        } else {
+            n.div_ceil(2)
+        };
+        let x = x.downcast_ref::<DynTensorValueType>()?;
+        // SAFETY: ORT stores the tensor contiguously with `n_bytes` bytes for `dtype`
+        let bytes = unsafe { std::slice::from_raw_parts(x.data_ptr() as *const u8, n_bytes) };
+
+        let y = match (Self::fp8(dtype), dtype) {
+            (Some(fp8), _) => XAny::from(Array::from_shape_vec(
+                dims,
+                bytes.iter().map(|&x| fp8.to_f32(x)).collect::<Vec<f32>>(),
+            )?),
+            (None, TensorElementType::Int4) => {
+                XAny::from(Array::from_shape_vec(dims, unpack_int4(bytes, n))?)
+            }
+            (None, TensorElementType::Uint4) => {
+                XAny::from(Array::from_shape_vec(dims, unpack_uint4(bytes, n))?)
+            }
+            _ => anyhow::bail!("Unsupported ort tensor type: {:?}", dtype),
+        };
+
+        Ok(y)
+    }
+
+    /// Builds the session on the first device in `devices` that works, falling back to `device`.
     fn build_session(&mut self, inputs: &OrtTensorAttr) -> Result<Session> {
+        if self.devices.is_empty() {
+            return self.build_session_on_device(inputs);
+        }
+
+        let devices = self.devices.clone();
+        let mut errs = Vec::with_capacity(devices.len());
+        for device in devices {
+            self.device = device;
+            match self.build_session_on_device(inputs) {
+                Ok(session) => {
+                    if !errs.is_empty() {
+                        info!("[{}] Falling back to {}", self.spec, device);
+                    }
+                    return Ok(session);
+                }
+                Err(err) => {
+                    warn!("[{}] Skipping {}: {}", self.spec, device, err);
+                    errs.push(format!("{}: {}", device, err));
+                }
+            }
+        }
+
+        anyhow::bail!(
+            "Failed to build session on any of the devices:\n{}",
+            errs.join("\n")
+        )
+    }
+
+    #[allow(unused_variables, clippy::only_used_in_recursion)]
+    fn build_session_on_device(&mut self, inputs: &OrtTensorAttr) -> Result<Session> {
         #[allow(unused_mut)]
         let mut builder = Session::builder()?;
         let compile_help = "Please compile ONNXRuntime with #EP";
@@ -889,22 +1777,137 @@ impl Engine {
             }
         }
 
+        // custom operators
+        for lib in self.operator_libraries.iter() {
+            builder = builder.with_operator_library(lib).map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to register custom operator library: {}. {}",
+                    lib,
+                    err
+                )
+            })?;
+        }
+
+        // prepacked weights
+        if let Some(weights) = &self.prepacked_weights {
+            builder = builder.with_prepacked_weights(&weights.0)?;
+        }
+
         // session
+        let graph_cache = self.graph_cache_file()?;
         let graph_opt_level = match self.graph_opt_level {
+            // already optimized
+            _ if matches!(graph_cache, Some((_, true))) => GraphOptimizationLevel::Disable,
             Some(0) => GraphOptimizationLevel::Disable,
             Some(1) => GraphOptimizationLevel::Level1,
             Some(2) => GraphOptimizationLevel::Level2,
             _ => GraphOptimizationLevel::Level3,
         };
-        let session = builder
+        let mut session = builder
             .with_optimization_level(graph_opt_level)?
             .with_intra_threads(self.num_intra_threads.unwrap_or(n_threads_available))?
-            .with_inter_threads(self.num_inter_threads.unwrap_or(2))?
-            .commit_from_file(self.file())?;
+            .with_inter_threads(self.num_inter_threads.unwrap_or(2))?;
+
+        // profiling
+        if self.profiling {
+            let prefix = crate::Dir::Cache
+                .crate_dir_default_with_subs(&["caches", "profiling"])?
+                .join(self.spec_file_stem());
+            session = session.with_profiling(prefix)?;
+        }
+
+        let session = match graph_cache {
+            Some((p, true)) => match session.commit_from_file(&p) {
+                Ok(session) => {
+                    debug!("[{}] Loaded optimized graph: {}", self.spec, p.display());
+                    session
+                }
+                Err(err) => {
+                    warn!(
+                        "[{}] Rebuilding the optimized graph {}: {}",
+                        self.spec,
+                        p.display(),
+                        err
+                    );
+                    std::fs::remove_file(&p)?;
+                    return self.build_session_on_device(inputs);
+                }
+            },
+            graph_cache => {
+                if let Some((p, _)) = &graph_cache {
+                    session = session.with_optimized_model_path(p)?;
+                }
+                match &self.bytes {
+                    Some(bytes) => session.commit_from_memory(bytes)?,
+                    None => session.commit_from_file(self.file())?,
+                }
+            }
+        };
 
         Ok(session)
     }
 
+    /// Returns the optimized graph file for the current device in `graph_cache`,
+    /// and whether it was already saved for this version of the model.
+    fn graph_cache_file(&self) -> Result<Option<(PathBuf, bool)>> {
+        let dir = match &self.graph_cache {
+            Some(dir) => PathBuf::from(dir),
+            None => return Ok(None),
+        };
+        if self.decryptor.is_some() {
+            warn!(
+                "[{}] Skipping the graph cache. The decrypted model would be saved unencrypted.",
+                self.spec
+            );
+            return Ok(None);
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        // optimizations can be device specific
+        let device = format!("{:?}", self.device).replace(|c: char| !c.is_ascii_alphanumeric(), "");
+        // the key follows the model: the content of in-memory models, the size and mtime of files
+        let mut hasher = DefaultHasher::new();
+        match &self.bytes {
+            Some(bytes) => hasher.write(bytes),
+            None => {
+                let meta = std::fs::metadata(self.file()).map_err(|err| {
+                    anyhow::anyhow!("Failed to read metadata of {}: {}", self.file(), err)
+                })?;
+                let mtime = meta.modified()?.duration_since(std::time::UNIX_EPOCH)?;
+                hasher.write_u64(meta.len());
+                hasher.write_u128(mtime.as_nanos());
+            }
+        }
+        let p = dir.join(format!(
+            "{}-{}-o{}-{:016x}.onnx",
+            self.spec_file_stem(),
+            device,
+            self.graph_opt_level.unwrap_or(3),
+            hasher.finish()
+        ));
+        let fresh = p.is_file();
+
+        Ok(Some((p, fresh)))
+    }
+
+    fn spec_file_stem(&self) -> String {
+        self.spec.replace(
+            |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '.',
+            "_",
+        )
+    }
+
+    /// Ends ORT session profiling and merges the profile into the `Perf` report under `spec`.
+    ///
+    /// Returns the profile file, or `None` if profiling is disabled or has already ended.
+    /// Profiling also ends when the engine is dropped.
+    pub fn end_profiling(&mut self) -> Result<Option<PathBuf>> {
+        match &mut self.onnx {
+            Some(onnx) => onnx.end_profiling(),
+            None => Ok(None),
+        }
+    }
+
     fn build_ort_inputs(xs: &OrtTensorAttr, iiixs: &[Iiix]) -> Result<Vec<Vec<MinOptMax>>> {
         // init
         let mut ys: Vec<Vec<MinOptMax>> = xs
@@ -959,6 +1962,68 @@ impl Engine {
         Ok(ys)
     }
 
+    /// Resolves the external data of an initializer, returning the number of bytes it occupies.
+    ///
+    /// Returns `None` if the initializer is stored inside the proto. The external file is
+    /// looked up relative to `dir`, which is `None` for models loaded from memory.
+    fn external_data_bytes(
+        tensor: &onnx::TensorProto,
+        dir: Option<&std::path::Path>,
+    ) -> Result<Option<usize>> {
+        if tensor.data_location != onnx::tensor_proto::DataLocation::External as i32 {
+            return Ok(None);
+        }
+
+        let (mut location, mut offset, mut length) = (None, 0, None);
+        for x in tensor.external_data.iter() {
+            match x.key.as_str() {
+                "location" => location = Some(x.value.as_str()),
+                "offset" => offset = x.value.parse::<usize>().unwrap_or_default(),
+                "length" => length = x.value.parse::<usize>().ok(),
+                _ => {}
+            }
+        }
+        let location = match location {
+            Some(x) => x,
+            None => anyhow::bail!(
+                "Invalid ONNX model: the external data of initializer `{}` has no location.",
+                tensor.name
+            ),
+        };
+        let dir = match dir {
+            Some(dir) => dir,
+            None => {
+                warn!(
+                    "Initializer `{}` refers to external data `{}`, which cannot be resolved for a model loaded from memory.",
+                    tensor.name, location
+                );
+                return Ok(length);
+            }
+        };
+        let p = dir.join(location);
+        let size = std::fs::metadata(&p)
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to find the external data file: {} (initializer `{}`). {}",
+                    p.display(),
+                    tensor.name,
+                    err
+                )
+            })?
+            .len() as usize;
+
+        Ok(Some(length.unwrap_or(size.saturating_sub(offset))))
+    }
+
+    /// Reads a value from the model's `metadata_props`.
+    pub(crate) fn fetch_from_proto(proto: &onnx::ModelProto, key: &str) -> Option<String> {
+        proto
+            .metadata_props
+            .iter()
+            .find(|x| x.key == key)
+            .map(|x| x.value.clone())
+    }
+
     fn get_ort_dtype_from_proto_dtype_id(value: i32) -> Option<TensorElementType> {
         match value {
             1 => Some(TensorElementType::Float32),
@@ -987,9 +2052,15 @@ impl Engine {
         }
     }
 
+    /// Reads the names, dtypes and dims of graph inputs or outputs.
+    ///
+    /// Entries without a tensor type, like intermediates exposed by name only, are resolved
+    /// with `fallback`, which is the ORT session metadata for outputs. Without it they are
+    /// rejected, so names, dtypes and dims always stay aligned.
     fn io_from_onnx_value_info(
         initializer_names: &HashSet<&str>,
         value_info: &[onnx::ValueInfoProto],
+        fallback: Option<&[ort::session::Output]>,
     ) -> Result<OrtTensorAttr> {
         let mut dimss: Vec<Vec<usize>> = Vec::new();
         let mut dtypes: Vec<TensorElementType> = Vec::new();
@@ -998,45 +2069,24 @@ impl Engine {
             if initializer_names.contains(v.name.as_str()) {
                 continue;
             }
-            names.push(v.name.to_string());
-            let dtype = match &v.r#type {
-                Some(dtype) => dtype,
-                None => continue,
-            };
-            let dtype = match &dtype.value {
-                Some(dtype) => dtype,
-                None => continue,
-            };
-            let tensor = match dtype {
-                onnx::type_proto::Value::TensorType(tensor) => tensor,
-                _ => continue,
-            };
-            let tensor_type = tensor.elem_type;
-            let tensor_type = match Self::get_ort_dtype_from_proto_dtype_id(tensor_type) {
-                Some(dtype) => dtype,
-                None => continue,
-            };
-            dtypes.push(tensor_type);
-
-            let shapes = match &tensor.shape {
-                Some(shapes) => shapes,
-                None => continue,
-            };
-            let mut shape_: Vec<usize> = Vec::new();
-            for shape in shapes.dim.iter() {
-                match &shape.value {
-                    None => continue,
-                    Some(value) => match value {
-                        onnx::tensor_shape_proto::dimension::Value::DimValue(x) => {
-                            shape_.push(*x as _);
-                        }
-                        onnx::tensor_shape_proto::dimension::Value::DimParam(_) => {
-                            shape_.push(0);
+            let (dtype, dims) = match Self::tensor_from_value_info(v) {
+                Some(x) => x,
+                None => match fallback.and_then(|xs| xs.iter().find(|x| x.name == v.name)) {
+                    Some(x) => match &x.output_type {
+                        ValueType::Tensor { ty, shape, .. } => {
+                            (*ty, shape.iter().map(|&x| x.max(0) as usize).collect())
                         }
+                        _ => anyhow::bail!("`{}` is not a tensor: {:?}", v.name, x.output_type),
                     },
-                }
-            }
-            dimss.push(shape_);
+                    None => anyhow::bail!(
+                        "Failed to resolve the type of `{}`. No tensor type found in the model.",
+                        v.name
+                    ),
+                },
+            };
+            names.push(v.name.to_string());
+            dtypes.push(dtype);
+            dimss.push(dims);
         }
         Ok(OrtTensorAttr {
             dimss,
@@ -1045,9 +2095,38 @@ impl Engine {
         })
     }
 
+    /// Dtype and dims of a value info, `None` unless it is a tensor of a known dtype with a shape.
+    fn tensor_from_value_info(v: &onnx::ValueInfoProto) -> Option<(TensorElementType, Vec<usize>)> {
+        let tensor = match v.r#type.as_ref()?.value.as_ref()? {
+            onnx::type_proto::Value::TensorType(tensor) => tensor,
+            _ => return None,
+        };
+        let dtype = Self::get_ort_dtype_from_proto_dtype_id(tensor.elem_type)?;
+        let mut shape_: Vec<usize> = Vec::new();
+        for shape in tensor.shape.as_ref()?.dim.iter() {
+            match &shape.value {
+                None => continue,
+                Some(value) => match value {
+                    onnx::tensor_shape_proto::dimension::Value::DimValue(x) => {
+                        shape_.push(*x as _);
+                    }
+                    onnx::tensor_shape_proto::dimension::Value::DimParam(_) => {
+                        shape_.push(0);
+                    }
+                },
+            }
+        }
+
+        Some((dtype, shape_))
+    }
+
     pub fn load_onnx<P: AsRef<std::path::Path>>(p: P) -> Result<onnx::ModelProto> {
         let f = std::fs::read(p.as_ref())?;
-        onnx::ModelProto::decode(f.as_slice()).map_err(|err| {
+        Self::load_onnx_from_bytes(&f)
+    }
+
+    pub fn load_onnx_from_bytes(x: &[u8]) -> Result<onnx::ModelProto> {
+        onnx::ModelProto::decode(x).map_err(|err| {
             anyhow::anyhow!(
                 "Failed to read the ONNX model: The file might be incomplete or corrupted. More detailed: {}",
                 err
@@ -1059,6 +2138,21 @@ impl Engine {
         &self.inputs_minoptmax[0][0]
     }
 
+    /// Largest batch the first input accepts: the batch dim of the model if it is fixed,
+    /// or the configured `max` if it is dynamic. `None` if dynamic and not configured.
+    pub fn max_batch(&self) -> Option<usize> {
+        let fixed = *self.onnx.as_ref()?.inputs.dimss.first()?.first()?;
+        if fixed != 0 {
+            return Some(fixed);
+        }
+
+        self.iiixs
+            .iter()
+            .any(|x| x.i == 0 && x.ii == 0)
+            .then(|| self.batch().max())
+            .filter(|&x| x > 0)
+    }
+
     pub fn is_batch_dyn(&self) -> bool {
         self.batch().is_dyn()
     }
@@ -1107,6 +2201,13 @@ impl Engine {
             .map(|x| x.proto.opset_import[0].version as usize)
     }
 
+    /// Op-type histogram, opsets and MACs of the loaded model.
+    pub fn summary(&self) -> Option<OnnxSummary> {
+        self.onnx
+            .as_ref()
+            .map(|x| OnnxInspector::summarize(&x.proto))
+    }
+
     pub fn producer_name(&self) -> Option<String> {
         self.onnx.as_ref().map(|x| x.proto.producer_name.clone())
     }
@@ -1192,3 +2293,270 @@ impl Engine {
         info!("{}", info);
     }
 }
+
+#[cfg(test)]
+mod tests_input_mismatch {
+    use super::InputMismatch;
+    use crate::MinOptMax;
+
+    #[test]
+    fn test_check_dims_ok() {
+        let expected = vec![
+            MinOptMax::from(1),
+            MinOptMax::from(3),
+            MinOptMax::from((320, 640, 1280)),
+        ];
+        assert!(InputMismatch::check_dims("images", &expected, &[1, 3, 320]).is_ok());
+        assert!(InputMismatch::check_dims("images", &expected, &[1, 3, 1280]).is_ok());
+    }
+
+    #[test]
+    fn test_check_dims_mismatch() {
+        let expected = vec![MinOptMax::from(1), MinOptMax::from((320, 640, 1280))];
+        let err = InputMismatch::check_dims("images", &expected, &[1, 2000]).unwrap_err();
+        assert_eq!(
+            err,
+            InputMismatch::Shape {
+                name: "images".to_string(),
+                expected: expected.clone(),
+                actual: vec![1, 2000],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Input `images` has dims [1, 2000], expected [1, 320..=1280]"
+        );
+        assert!(InputMismatch::check_dims("images", &expected, &[1, 640, 1]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_engine {
+    use super::*;
+    use crate::onnx::{
+        tensor_proto::DataType, tensor_shape_proto, type_proto, TensorShapeProto, TypeProto,
+        ValueInfoProto,
+    };
+
+    fn value_info(name: &str) -> ValueInfoProto {
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: DataType::Float as i32,
+                    shape: Some(TensorShapeProto {
+                        dim: vec![
+                            tensor_shape_proto::Dimension {
+                                value: Some(tensor_shape_proto::dimension::Value::DimParam(
+                                    "batch".to_string(),
+                                )),
+                                ..Default::default()
+                            },
+                            tensor_shape_proto::Dimension {
+                                value: Some(tensor_shape_proto::dimension::Value::DimValue(3)),
+                                ..Default::default()
+                            },
+                        ],
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn add(a: &str, b: &str, y: &str) -> onnx::NodeProto {
+        onnx::NodeProto {
+            input: vec![a.to_string(), b.to_string()],
+            output: vec![y.to_string()],
+            op_type: "Add".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn model(node: Vec<onnx::NodeProto>) -> onnx::ModelProto {
+        onnx::ModelProto {
+            ir_version: 8,
+            opset_import: vec![onnx::OperatorSetIdProto {
+                domain: String::new(),
+                version: 17,
+            }],
+            graph: Some(onnx::GraphProto {
+                name: "add".to_string(),
+                node,
+                input: vec![value_info("x")],
+                output: vec![value_info("y")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    // x[batch, 3] -> Add(x, x) -> y
+    fn engine(config: ORTConfig) -> Engine {
+        let bytes = model(vec![add("x", "x", "y")]).encode_to_vec();
+        Engine::try_from_config(&config.with_bytes(bytes).with_num_dry_run(0)).unwrap()
+    }
+
+    #[test]
+    fn test_io_binding_rebinds() {
+        let mut engine = engine(ORTConfig::default().with_io_binding(true));
+        let run = |engine: &mut Engine, xs: Vec<f32>| -> Vec<f32> {
+            let x = X::from_shape_vec(&[xs.len() / 3, 3], xs).unwrap();
+            let ys = engine.run(x.into()).unwrap();
+            ys[0].0.iter().copied().collect()
+        };
+
+        assert_eq!(run(&mut engine, vec![1., 2., 3.]), [2., 4., 6.]);
+        assert_eq!(run(&mut engine, vec![4., 5., 6.]), [8., 10., 12.]);
+        assert_eq!(
+            run(&mut engine, vec![1., 1., 1., 2., 2., 2.]),
+            [2., 2., 2., 4., 4., 4.]
+        );
+
+        // `y` shares the `batch` dim of `x`, so it is preallocated
+        let session = &engine.onnx.as_ref().unwrap().session;
+        assert_eq!(
+            Engine::resolve_output_dims(session, &[vec![2, 3]]),
+            [Some(vec![2, 3])]
+        );
+    }
+
+    #[test]
+    fn test_run_named_checks() {
+        let mut engine = engine(ORTConfig::default());
+        let run = |engine: &mut Engine, x: XAny| {
+            let mut xs = XAnys::default();
+            xs.push_kv("x", x).unwrap();
+            engine.run_named(xs)
+        };
+
+        // the batch is dynamic and not configured, so any size is accepted
+        let x = Array::from_shape_vec(vec![4, 3], (0..12u8).collect()).unwrap();
+        let ys = run(&mut engine, XAny::from(x)).unwrap();
+        assert_eq!(ys["y"].to_x().0.shape(), [4, 3]);
+        assert_eq!(ys["y"].to_x().0[[3, 2]], 22.);
+
+        let err = run(&mut engine, X::zeros(&[4, 2]).into()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<InputMismatch>(),
+            Some(InputMismatch::Shape { .. })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Input `x` has dims [4, 2], expected [0.., 3]"
+        );
+
+        // int64 does not fit in fp32 exactly, even if these values would
+        let x = Array::from_shape_vec(vec![1, 3], vec![1i64, 2, 3]).unwrap();
+        let err = run(&mut engine, XAny::from(x)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InputMismatch>(),
+            Some(&InputMismatch::DType {
+                name: "x".to_string(),
+                expected: DType::Fp32,
+                actual: DType::Int64,
+            })
+        );
+    }
+
+    #[test]
+    fn test_lossless_cast() {
+        assert!(DType::Uint8.is_lossless_cast_to(&DType::Fp16));
+        assert!(DType::Uint16.is_lossless_cast_to(&DType::Int32));
+        assert!(DType::Int32.is_lossless_cast_to(&DType::Fp64));
+        assert!(DType::Bf16.is_lossless_cast_to(&DType::Fp32));
+        assert!(DType::Bool.is_lossless_cast_to(&DType::Uint8));
+        assert!(!DType::Int8.is_lossless_cast_to(&DType::Uint64));
+        assert!(!DType::Int32.is_lossless_cast_to(&DType::Fp32));
+        assert!(!DType::Bf16.is_lossless_cast_to(&DType::Fp16));
+        assert!(!DType::Fp32.is_lossless_cast_to(&DType::Int64));
+    }
+
+    // x[batch, 3] -> Add(x, x) -> h -> Add(h, x) -> y, with `h` exposed by name only
+    #[test]
+    fn test_untyped_outputs() {
+        let proto = model(vec![add("x", "x", "h"), add("h", "x", "y")]);
+        let bytes = OnnxInspector::from_proto(proto)
+            .with_outputs(&["h"])
+            .unwrap()
+            .to_bytes();
+        let config = ORTConfig::default().with_bytes(bytes).with_num_dry_run(0);
+        let mut engine = Engine::try_from_config(&config).unwrap();
+        assert_eq!(engine.onames().unwrap(), ["y", "h"]);
+        assert_eq!(
+            engine.onnx().unwrap().outputs.dtypes,
+            [TensorElementType::Float32; 2]
+        );
+        assert_eq!(engine.onnx().unwrap().outputs.dimss, [[0, 3], [0, 3]]);
+
+        let ys = engine.run(X::ones(&[2, 3]).into()).unwrap();
+        assert_eq!(ys["y"].0.sum(), 18.);
+        assert_eq!(ys["h"].0.sum(), 12.);
+    }
+
+    #[test]
+    fn test_graph_cache_file_from_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = |node| {
+            let config = ORTConfig::default()
+                .with_bytes(model(node).encode_to_vec())
+                .with_graph_cache(dir.path().to_str().unwrap());
+            Engine::from_config(&config)
+                .graph_cache_file()
+                .unwrap()
+                .unwrap()
+        };
+
+        let (p, fresh) = cache_file(vec![add("x", "x", "y")]);
+        assert!(!fresh);
+        std::fs::write(&p, b"").unwrap();
+        assert_eq!(cache_file(vec![add("x", "x", "y")]), (p.clone(), true));
+
+        let (p_, fresh) = cache_file(vec![add("x", "x", "h"), add("h", "x", "y")]);
+        assert_ne!(p_, p);
+        assert!(!fresh);
+    }
+
+    #[test]
+    fn test_graph_cache_file_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("add.onnx");
+        let cache_file = || {
+            let config = ORTConfig::default()
+                .with_file(file.to_str().unwrap())
+                .with_graph_cache(dir.path().join("cache").to_str().unwrap());
+            Engine::from_config(&config)
+                .graph_cache_file()
+                .unwrap()
+                .unwrap()
+        };
+
+        std::fs::write(&file, model(vec![add("x", "x", "y")]).encode_to_vec()).unwrap();
+        let (p, fresh) = cache_file();
+        assert!(!fresh);
+        std::fs::write(&p, b"").unwrap();
+        assert_eq!(cache_file(), (p.clone(), true));
+
+        // a model of another size is a cache miss, whatever its mtime
+        let proto = model(vec![add("x", "x", "h"), add("h", "x", "y")]);
+        std::fs::write(&file, proto.encode_to_vec()).unwrap();
+        let (p_, fresh) = cache_file();
+        assert_ne!(p_, p);
+        assert!(!fresh);
+    }
+
+    #[test]
+    fn test_cancelled_run() {
+        let token = CancelToken::new();
+        let mut engine = engine(ORTConfig::default()).with_cancel_token(token.clone());
+        assert!(engine.run(X::ones(&[1, 3]).into()).is_ok());
+
+        token.cancel();
+        let err = engine.run(X::ones(&[1, 3]).into()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<crate::Interruption>(),
+            Some(&crate::Interruption::Cancelled)
+        );
+    }
+}
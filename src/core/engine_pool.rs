@@ -0,0 +1,48 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::{Engine, ORTConfig, Pool, SharedWeights, XAnys, Xs};
+
+/// A pool of sessions built from the same [`ORTConfig`], usable from multiple threads.
+pub type EnginePool = Pool<Engine>;
+
+impl Pool<Engine> {
+    /// Builds `size` engines from `config`.
+    ///
+    /// The ONNX proto is loaded (and decrypted) once and shared by all engines.
+    /// With `share_weights`, the sessions also share one prepacked weights container,
+    /// so constant weights are prepacked only once.
+    pub fn try_from_config(config: &ORTConfig, size: usize, share_weights: bool) -> Result<Self> {
+        let mut engine = Engine::from_config(config);
+        let proto = Arc::new(engine.load_proto()?);
+        let bytes = engine.bytes;
+        let weights = share_weights.then(SharedWeights::new);
+
+        Self::try_new(size, |i| {
+            let mut engine = Engine::from_config(config);
+            engine.bytes = bytes.clone();
+            if let Some(weights) = &weights {
+                engine = engine.with_prepacked_weights(weights.clone());
+            }
+            if i > 0 {
+                engine.spec = format!("{}#{}", engine.spec, i);
+            }
+            engine.build_with_proto(proto.clone())
+        })
+    }
+
+    /// Runs on the next idle engine, blocking until one is available.
+    pub fn run(&self, xs: Xs) -> Result<Xs> {
+        self.get().run(xs)
+    }
+
+    /// Runs on the next idle engine, fetching only the outputs in `names`.
+    pub fn run_with_outputs(&self, xs: Xs, names: &[&str]) -> Result<Xs> {
+        self.get().run_with_outputs(xs, names)
+    }
+
+    /// Runs typed inputs on the next idle engine, blocking until one is available.
+    pub fn run_any<T: Into<XAnys>>(&self, xs: T) -> Result<XAnys> {
+        self.get().run_any(xs)
+    }
+}
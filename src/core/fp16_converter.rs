@@ -0,0 +1,554 @@
+use aksr::Builder;
+use anyhow::Result;
+use half::f16;
+use log::info;
+use prost::Message;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    onnx::{
+        self,
+        attribute_proto::AttributeType,
+        tensor_proto::{DataLocation, DataType},
+        type_proto,
+    },
+    try_fetch_file_stem, Engine,
+};
+
+const FLOAT: i32 = DataType::Float as i32;
+const FLOAT16: i32 = DataType::Float16 as i32;
+const INT64: i32 = DataType::Int64 as i32;
+
+/// Offline FP32 to FP16 conversion of ONNX models.
+///
+/// Float initializers, constants and `value_info` are rewritten to fp16. The model inputs and
+/// outputs stay fp32 behind inserted `Cast` nodes, and ops in the block-list keep running in
+/// fp32 with casts around them.
+///
+/// Types of intermediate tensors are taken from `value_info` when present and otherwise
+/// propagated from node inputs, so shape-inferred models convert most reliably. Subgraphs of
+/// control-flow ops are left untouched.
+#[derive(Builder, Debug, Clone)]
+pub struct Fp16Converter {
+    /// Op types kept in fp32.
+    #[args(except(setter))]
+    block_list: Vec<String>,
+}
+
+impl Default for Fp16Converter {
+    fn default() -> Self {
+        Self {
+            block_list: Self::DEFAULT_BLOCK_LIST
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Fp16Converter {
+    /// Ops that lose too much precision or have no fp16 kernels in common EPs.
+    pub const DEFAULT_BLOCK_LIST: &'static [&'static str] = &[
+        "Resize",
+        "Upsample",
+        "Range",
+        "CumSum",
+        "TopK",
+        "NonMaxSuppression",
+        "RoiAlign",
+        "RandomUniform",
+        "RandomUniformLike",
+        "RandomNormal",
+        "RandomNormalLike",
+        "LayerNormalization",
+        "InstanceNormalization",
+        "GroupNormalization",
+        "LpNormalization",
+        "MeanVarianceNormalization",
+    ];
+
+    /// Ops whose outputs do not share the type of their first input.
+    const TYPE_CHANGING: &'static [&'static str] = &[
+        "Shape",
+        "Size",
+        "ArgMax",
+        "ArgMin",
+        "NonZero",
+        "NonMaxSuppression",
+        "Equal",
+        "Less",
+        "LessOrEqual",
+        "Greater",
+        "GreaterOrEqual",
+        "Not",
+        "And",
+        "Or",
+        "Xor",
+        "IsNaN",
+        "IsInf",
+        "QuantizeLinear",
+        "DynamicQuantizeLinear",
+        "OneHot",
+        "Multinomial",
+        "Bernoulli",
+    ];
+
+    /// Ops that accept inputs of any type, which are therefore never cast.
+    const TYPE_AGNOSTIC: &'static [&'static str] = &["Cast", "Shape", "Size"];
+
+    /// Keeps ops of type `x` in fp32.
+    pub fn with_block_op(mut self, x: &str) -> Self {
+        if !self.block_list.iter().any(|y| y == x) {
+            self.block_list.push(x.to_string());
+        }
+        self
+    }
+
+    /// Removes `x` from the block-list.
+    pub fn without_block_op(mut self, x: &str) -> Self {
+        self.block_list.retain(|y| y != x);
+        self
+    }
+
+    /// Converts a model file and writes `<stem>-fp16.onnx` next to it,
+    /// the file name [`crate::ORTConfig::try_commit`] looks for with `DType::Fp16`.
+    pub fn convert_file<P: AsRef<Path>>(&self, p: P) -> Result<PathBuf> {
+        let p = p.as_ref();
+        let model = self.convert(Engine::load_onnx(p)?)?;
+        let dst = p.with_file_name(format!("{}-fp16.onnx", try_fetch_file_stem(p)?));
+        std::fs::write(&dst, model.encode_to_vec()).map_err(|err| {
+            anyhow::anyhow!("Failed to write the fp16 model: {}. {}", dst.display(), err)
+        })?;
+        info!("FP16 model saved: {}", dst.display());
+
+        Ok(dst)
+    }
+
+    /// Converts a serialized model.
+    pub fn convert_bytes(&self, x: &[u8]) -> Result<Vec<u8>> {
+        Ok(self
+            .convert(Engine::load_onnx_from_bytes(x)?)?
+            .encode_to_vec())
+    }
+
+    pub(crate) fn convert(&self, mut model: onnx::ModelProto) -> Result<onnx::ModelProto> {
+        let graph = match model.graph.as_mut() {
+            Some(graph) => graph,
+            None => anyhow::bail!("Failed to convert the model to fp16. No graph found."),
+        };
+        let mut types = Self::infer_types(graph);
+        let blocked: Vec<bool> = graph
+            .node
+            .iter()
+            .map(|x| self.block_list.contains(&x.op_type))
+            .collect();
+
+        // Outputs computed in fp16 are renamed and cast back to fp32 at the end
+        let mut renames = HashMap::new();
+        let mut tail = Vec::new();
+        for output in graph.output.iter() {
+            if types.get(&output.name) != Some(&FLOAT) {
+                continue;
+            }
+            let producer = graph
+                .node
+                .iter()
+                .position(|x| x.output.contains(&output.name));
+            if matches!(producer, Some(i) if !blocked[i]) {
+                let name = format!("{}_fp16", output.name);
+                tail.push(Self::cast(&name, &output.name, FLOAT));
+                renames.insert(output.name.clone(), name);
+            }
+        }
+        for node in graph.node.iter_mut() {
+            for x in node.input.iter_mut().chain(node.output.iter_mut()) {
+                if let Some(y) = renames.get(x) {
+                    *x = y.clone();
+                }
+            }
+        }
+        for x in graph.value_info.iter_mut() {
+            if let Some(y) = renames.get(&x.name) {
+                x.name = y.clone();
+            }
+        }
+        for y in renames.into_values() {
+            types.insert(y, FLOAT);
+        }
+
+        // Float tensors that stay fp32: model inputs, outputs of blocked ops,
+        // and initializers only consumed by blocked ops
+        let mut fp32: HashSet<String> = graph
+            .input
+            .iter()
+            .filter(|x| types.get(&x.name) == Some(&FLOAT))
+            .map(|x| x.name.clone())
+            .collect();
+        let mut fp16_consumed = HashSet::new();
+        for (node, &blocked) in graph.node.iter().zip(blocked.iter()) {
+            if blocked {
+                fp32.extend(
+                    node.output
+                        .iter()
+                        .filter(|x| types.get(*x) == Some(&FLOAT))
+                        .cloned(),
+                );
+            } else {
+                fp16_consumed.extend(node.input.iter().cloned());
+            }
+        }
+        for x in graph.initializer.iter_mut() {
+            if x.data_type != FLOAT || fp32.contains(&x.name) {
+                continue;
+            }
+            let consumed = graph.node.iter().any(|n| n.input.contains(&x.name));
+            if consumed && !fp16_consumed.contains(&x.name) {
+                fp32.insert(x.name.clone());
+            } else {
+                Self::convert_tensor(x)?;
+            }
+        }
+
+        // Rewrite nodes, casting inputs stored in the other precision
+        let mut nodes = Vec::with_capacity(graph.node.len() + tail.len());
+        let mut casts: HashMap<(String, i32), String> = HashMap::new();
+        for (mut node, blocked) in std::mem::take(&mut graph.node).into_iter().zip(blocked) {
+            if !blocked {
+                Self::convert_attributes(&mut node)?;
+            }
+            if !Self::TYPE_AGNOSTIC.contains(&node.op_type.as_str()) {
+                for x in node.input.iter_mut() {
+                    if types.get(x.as_str()) != Some(&FLOAT) || fp32.contains(x.as_str()) == blocked
+                    {
+                        continue;
+                    }
+                    let to = if blocked { FLOAT } else { FLOAT16 };
+                    let key = (x.clone(), to);
+                    if let Some(y) = casts.get(&key) {
+                        *x = y.clone();
+                    } else {
+                        let y = format!("{}_cast_{}", x, if blocked { "fp32" } else { "fp16" });
+                        nodes.push(Self::cast(x, &y, to));
+                        casts.insert(key, y.clone());
+                        *x = y;
+                    }
+                }
+            }
+            nodes.push(node);
+        }
+        nodes.extend(tail);
+        graph.node = nodes;
+
+        for x in graph.value_info.iter_mut() {
+            if !fp32.contains(&x.name) {
+                if let Some(type_proto::Value::TensorType(t)) =
+                    x.r#type.as_mut().and_then(|t| t.value.as_mut())
+                {
+                    if t.elem_type == FLOAT {
+                        t.elem_type = FLOAT16;
+                    }
+                }
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Element types of the tensors in `graph`, as far as they can be told without shape inference.
+    fn infer_types(graph: &onnx::GraphProto) -> HashMap<String, i32> {
+        let mut types = HashMap::new();
+        for x in graph
+            .input
+            .iter()
+            .chain(graph.output.iter())
+            .chain(graph.value_info.iter())
+        {
+            if let Some(type_proto::Value::TensorType(t)) =
+                x.r#type.as_ref().and_then(|t| t.value.as_ref())
+            {
+                types.insert(x.name.clone(), t.elem_type);
+            }
+        }
+        for x in graph.initializer.iter() {
+            types.insert(x.name.clone(), x.data_type);
+        }
+
+        for node in graph.node.iter() {
+            let input_type = |i: usize| node.input.get(i).and_then(|x| types.get(x)).copied();
+            let ty = match node.op_type.as_str() {
+                "Cast" => Self::attribute(node, "to").map(|x| x.i as i32),
+                "CastLike" | "Where" | "DequantizeLinear" => input_type(1),
+                "Constant" => node.attribute.first().and_then(|x| match x.name.as_str() {
+                    "value" => x.t.as_ref().map(|t| t.data_type),
+                    "value_float" | "value_floats" => Some(FLOAT),
+                    "value_int" | "value_ints" => Some(INT64),
+                    _ => None,
+                }),
+                "ConstantOfShape" => Some(
+                    Self::attribute(node, "value")
+                        .and_then(|x| x.t.as_ref())
+                        .map_or(FLOAT, |t| t.data_type),
+                ),
+                "RandomUniform" | "RandomNormal" => {
+                    Some(Self::attribute(node, "dtype").map_or(FLOAT, |x| x.i as i32))
+                }
+                x if Self::TYPE_CHANGING.contains(&x) => None,
+                _ => input_type(0),
+            };
+            let n = match node.op_type.as_str() {
+                "Split" | "LSTM" | "GRU" | "RNN" => node.output.len(),
+                _ => 1,
+            };
+            if let Some(ty) = ty {
+                for y in node.output.iter().take(n).filter(|x| !x.is_empty()) {
+                    types.entry(y.clone()).or_insert(ty);
+                }
+            }
+        }
+
+        types
+    }
+
+    fn convert_attributes(node: &mut onnx::NodeProto) -> Result<()> {
+        match node.op_type.as_str() {
+            "Cast" => {
+                for x in node.attribute.iter_mut() {
+                    if x.name == "to" && x.i == FLOAT as i64 {
+                        x.i = FLOAT16 as i64;
+                    }
+                }
+            }
+            "Constant" | "ConstantOfShape" => {
+                for x in node.attribute.iter_mut() {
+                    match x.name.as_str() {
+                        "value" => {
+                            if let Some(t) = x.t.as_mut() {
+                                Self::convert_tensor(t)?;
+                            }
+                        }
+                        "value_float" | "value_floats" => {
+                            let (dims, float_data) = match x.name.as_str() {
+                                "value_float" => (vec![], vec![x.f]),
+                                _ => (vec![x.floats.len() as i64], x.floats.clone()),
+                            };
+                            let mut t = onnx::TensorProto {
+                                dims,
+                                data_type: FLOAT,
+                                float_data,
+                                ..Default::default()
+                            };
+                            Self::convert_tensor(&mut t)?;
+                            *x = Self::tensor_attribute(t);
+                        }
+                        _ => {}
+                    }
+                }
+
+                // ConstantOfShape defaults to fp32 zeros
+                if node.op_type == "ConstantOfShape" && Self::attribute(node, "value").is_none() {
+                    let mut t = onnx::TensorProto {
+                        dims: vec![1],
+                        data_type: FLOAT,
+                        float_data: vec![0.],
+                        ..Default::default()
+                    };
+                    Self::convert_tensor(&mut t)?;
+                    node.attribute.push(Self::tensor_attribute(t));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn convert_tensor(x: &mut onnx::TensorProto) -> Result<()> {
+        if x.data_type != FLOAT {
+            return Ok(());
+        }
+        if x.data_location == DataLocation::External as i32 {
+            anyhow::bail!(
+                "Failed to convert `{}` to fp16. Tensors in external data are not supported.",
+                x.name
+            );
+        }
+
+        let xs: Vec<f32> = if x.raw_data.is_empty() {
+            std::mem::take(&mut x.float_data)
+        } else {
+            x.raw_data
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        };
+        x.raw_data = xs
+            .iter()
+            .flat_map(|&x| {
+                f16::from_f32(x.clamp(f16::MIN.to_f32(), f16::MAX.to_f32())).to_le_bytes()
+            })
+            .collect();
+        x.float_data.clear();
+        x.data_type = FLOAT16;
+
+        Ok(())
+    }
+
+    fn cast(x: &str, y: &str, to: i32) -> onnx::NodeProto {
+        onnx::NodeProto {
+            input: vec![x.to_string()],
+            output: vec![y.to_string()],
+            name: y.to_string(),
+            op_type: "Cast".to_string(),
+            attribute: vec![onnx::AttributeProto {
+                name: "to".to_string(),
+                r#type: AttributeType::Int as i32,
+                i: to as i64,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn tensor_attribute(t: onnx::TensorProto) -> onnx::AttributeProto {
+        onnx::AttributeProto {
+            name: "value".to_string(),
+            r#type: AttributeType::Tensor as i32,
+            t: Some(t),
+            ..Default::default()
+        }
+    }
+
+    fn attribute<'a>(node: &'a onnx::NodeProto, name: &str) -> Option<&'a onnx::AttributeProto> {
+        node.attribute.iter().find(|x| x.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests_fp16_converter {
+    use super::*;
+
+    fn value_info(name: &str, elem_type: i32) -> onnx::ValueInfoProto {
+        onnx::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(onnx::TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type,
+                    shape: None,
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn node(op_type: &str, input: &[&str], output: &[&str]) -> onnx::NodeProto {
+        onnx::NodeProto {
+            input: input.iter().map(|x| x.to_string()).collect(),
+            output: output.iter().map(|x| x.to_string()).collect(),
+            op_type: op_type.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn initializer(name: &str, xs: Vec<f32>) -> onnx::TensorProto {
+        onnx::TensorProto {
+            name: name.to_string(),
+            dims: vec![xs.len() as i64],
+            data_type: FLOAT,
+            float_data: xs,
+            ..Default::default()
+        }
+    }
+
+    fn to_model(graph: onnx::GraphProto) -> onnx::ModelProto {
+        onnx::ModelProto {
+            graph: Some(graph),
+            ..Default::default()
+        }
+    }
+
+    fn ops(model: &onnx::ModelProto) -> Vec<(String, Vec<String>, Vec<String>)> {
+        model
+            .graph
+            .as_ref()
+            .unwrap()
+            .node
+            .iter()
+            .map(|x| (x.op_type.clone(), x.input.clone(), x.output.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_io_casts() {
+        let graph = onnx::GraphProto {
+            node: vec![node("Add", &["x", "w"], &["y"])],
+            initializer: vec![initializer("w", vec![1., 1e6])],
+            input: vec![value_info("x", FLOAT)],
+            output: vec![value_info("y", FLOAT)],
+            ..Default::default()
+        };
+        let model = Fp16Converter::default().convert(to_model(graph)).unwrap();
+        let s = |x: &str| x.to_string();
+        assert_eq!(
+            ops(&model),
+            vec![
+                (s("Cast"), vec![s("x")], vec![s("x_cast_fp16")]),
+                (s("Add"), vec![s("x_cast_fp16"), s("w")], vec![s("y_fp16")]),
+                (s("Cast"), vec![s("y_fp16")], vec![s("y")]),
+            ]
+        );
+
+        let graph = model.graph.unwrap();
+        let w = &graph.initializer[0];
+        assert_eq!(w.data_type, FLOAT16);
+        assert_eq!(
+            w.raw_data,
+            [f16::from_f32(1.), f16::MAX]
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect::<Vec<u8>>()
+        );
+        assert_eq!(graph.input, vec![value_info("x", FLOAT)]);
+        assert_eq!(graph.output, vec![value_info("y", FLOAT)]);
+    }
+
+    #[test]
+    fn test_block_list() {
+        let graph = || onnx::GraphProto {
+            node: vec![
+                node("Resize", &["x", "", "scales"], &["z"]),
+                node("Relu", &["z"], &["y"]),
+            ],
+            initializer: vec![initializer("scales", vec![1., 1., 2., 2.])],
+            input: vec![value_info("x", FLOAT)],
+            output: vec![value_info("y", FLOAT)],
+            value_info: vec![value_info("z", FLOAT)],
+            ..Default::default()
+        };
+        let model = Fp16Converter::default().convert(to_model(graph())).unwrap();
+        let s = |x: &str| x.to_string();
+        assert_eq!(
+            ops(&model),
+            vec![
+                (s("Resize"), vec![s("x"), s(""), s("scales")], vec![s("z")]),
+                (s("Cast"), vec![s("z")], vec![s("z_cast_fp16")]),
+                (s("Relu"), vec![s("z_cast_fp16")], vec![s("y_fp16")]),
+                (s("Cast"), vec![s("y_fp16")], vec![s("y")]),
+            ]
+        );
+
+        // scales only feeds the blocked Resize
+        let converted = model.graph.unwrap();
+        assert_eq!(converted.initializer[0].data_type, FLOAT);
+        assert_eq!(converted.value_info, vec![value_info("z", FLOAT)]);
+
+        let model = Fp16Converter::default()
+            .without_block_op("Resize")
+            .convert(to_model(graph()))
+            .unwrap();
+        let converted = model.graph.unwrap();
+        assert_eq!(converted.initializer[0].data_type, FLOAT16);
+        assert_eq!(converted.value_info, vec![value_info("z", FLOAT16)]);
+    }
+}
@@ -0,0 +1,53 @@
+use aksr::Builder;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Where a video or stream frame comes from and when it was decoded.
+///
+/// Set by [`crate::DataLoader`] on decoded frames and copied to the [`crate::Y`] of each
+/// image by the models, so results can be aligned with the video time.
+#[derive(Builder, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameMeta {
+    /// Position of the frame in the stream, counting the skipped frames.
+    /// Estimated from the timestamp when the video was seeked.
+    index: u64,
+    /// Presentation timestamp, in seconds.
+    pts: f64,
+    /// Id of the source, to tell frames of several streams apart.
+    stream_id: usize,
+    /// Frame rate reported by the source.
+    fps: f32,
+    /// Wall-clock time at which the frame was decoded.
+    decoded_at: SystemTime,
+}
+
+impl Default for FrameMeta {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            pts: 0.,
+            stream_id: 0,
+            fps: 0.,
+            decoded_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_frame_meta {
+    use super::FrameMeta;
+    use crate::{keep_frames, Image, Y};
+
+    #[test]
+    fn test_keep_frames() {
+        let frame = FrameMeta::default().with_index(3).with_pts(0.12);
+        let xs = vec![Image::default().with_frame(frame.clone()), Image::default()];
+
+        let ys = keep_frames(&xs, vec![Y::default(), Y::default()]);
+        assert_eq!(ys[0].frame(), Some(&frame));
+        assert_eq!(ys[1].frame(), None);
+
+        let ys = keep_frames(&xs, vec![Y::default()]);
+        assert_eq!(ys[0].frame(), None);
+    }
+}
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 /// CPU execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CpuConfig {
     /// Enable CPU arena allocator for memory management.
     pub arena_allocator: bool,
@@ -14,6 +15,7 @@ pub struct CpuConfig {
 
 /// NVIDIA TensorRT execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TensorRtConfig {
     /// Enable FP16 precision for faster inference.
     pub fp16: bool,
@@ -25,6 +27,7 @@ pub struct TensorRtConfig {
 
 /// Intel OpenVINO execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct OpenVinoConfig {
     /// Enable dynamic shapes support.
     pub dynamic_shapes: bool,
@@ -38,6 +41,7 @@ pub struct OpenVinoConfig {
 
 /// Intel oneDNN execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct OneDnnConfig {
     /// Enable arena allocator for memory management.
     pub arena_allocator: bool,
@@ -45,6 +49,7 @@ pub struct OneDnnConfig {
 
 /// Apple CoreML execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CoreMlConfig {
     /// Use static input shapes for optimization.
     pub static_input_shapes: bool,
@@ -54,6 +59,7 @@ pub struct CoreMlConfig {
 
 /// Huawei CANN execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CannConfig {
     /// Enable graph inference mode.
     pub graph_inference: bool,
@@ -65,6 +71,7 @@ pub struct CannConfig {
 
 /// Android NNAPI execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct NnapiConfig {
     /// Force CPU-only execution.
     pub cpu_only: bool,
@@ -78,6 +85,7 @@ pub struct NnapiConfig {
 
 /// ARM NN execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ArmNnConfig {
     /// Enable arena allocator for memory management.
     pub arena_allocator: bool,
@@ -85,6 +93,7 @@ pub struct ArmNnConfig {
 
 /// AMD MIGraphX execution provider configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct MiGraphXConfig {
     /// Enable FP16 precision.
     pub fp16: bool,
@@ -94,6 +103,7 @@ pub struct MiGraphXConfig {
 
 /// Unified hardware configuration containing all execution provider configs.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct HardwareConfig {
     /// CPU execution provider configuration.
     pub cpu: CpuConfig,
@@ -346,6 +346,56 @@ impl Hub {
         Ok(body)
     }
 
+    /// Fetches a model file together with its external data sidecar files.
+    ///
+    /// Sidecars are the release assets next to the model named `<file>_data`, `<file>.data`
+    /// or `<stem>.data`, possibly followed by a shard suffix. They are stored in the same
+    /// directory as the model, so external data locations resolve relative to it.
+    /// Local files are returned as-is.
+    pub fn try_fetch_with_sidecars(&mut self, s: &str) -> Result<String> {
+        let saveout = self.try_fetch(s)?;
+        if PathBuf::from(s).exists() {
+            return Ok(saveout);
+        }
+
+        let sidecars: Vec<String> = match Self::is_valid_github_release_url(s) {
+            Some((owner, repo, tag, file_name)) => Self::new(&owner, &repo)
+                .files(&tag)
+                .into_iter()
+                .filter(|x| Self::is_sidecar(&file_name, x))
+                .map(|x| {
+                    format!(
+                        "https://github.com/{}/{}/releases/download/{}/{}",
+                        owner, repo, tag, x
+                    )
+                })
+                .collect(),
+            None => match s.split_once('/') {
+                Some((tag, file_name)) => self
+                    .files(tag)
+                    .into_iter()
+                    .filter(|x| Self::is_sidecar(file_name, x))
+                    .map(|x| format!("{}/{}", tag, x))
+                    .collect(),
+                None => vec![],
+            },
+        };
+        for sidecar in sidecars.iter() {
+            let p = self.try_fetch(sidecar)?;
+            log::debug!("Successfully fetched external data file: {}", p);
+        }
+
+        Ok(saveout)
+    }
+
+    fn is_sidecar(file_name: &str, x: &str) -> bool {
+        let stem = file_name.strip_suffix(".onnx").unwrap_or(file_name);
+        x != file_name
+            && (x.starts_with(&format!("{}_data", file_name))
+                || x.starts_with(&format!("{}.data", file_name))
+                || x.starts_with(&format!("{}.data", stem)))
+    }
+
     pub fn tags(&self) -> Vec<String> {
         self.get_releases(&self.owner, &self.repo, &self.to, &self.ttl)
             .unwrap_or_default()
@@ -529,3 +579,19 @@ impl Hub {
         self
     }
 }
+
+#[cfg(test)]
+mod tests_hub {
+    use super::Hub;
+
+    #[test]
+    fn test_is_sidecar() {
+        assert!(Hub::is_sidecar("model.onnx", "model.onnx_data"));
+        assert!(Hub::is_sidecar("model.onnx", "model.onnx.data"));
+        assert!(Hub::is_sidecar("model.onnx", "model.data"));
+        assert!(Hub::is_sidecar("model.onnx", "model.onnx_data_1"));
+        assert!(!Hub::is_sidecar("model.onnx", "model.onnx"));
+        assert!(!Hub::is_sidecar("model.onnx", "model-fp16.onnx"));
+        assert!(!Hub::is_sidecar("model.onnx", "other.onnx_data"));
+    }
+}
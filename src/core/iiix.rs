@@ -1,7 +1,10 @@
+use serde::{Deserialize, Serialize};
+
 use crate::MinOptMax;
 
 /// A struct for input composed of the i-th input, the ii-th dimension, and the value.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Iiix {
     /// Input index.
     pub i: usize,
@@ -5,9 +5,10 @@ use fast_image_resize::{
     pixels::PixelType,
 };
 use image::{DynamicImage, GrayImage, RgbImage, RgbaImage, SubImage};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-use crate::{build_resizer_filter, Hub, Location, MediaType, X};
+use crate::{build_resizer_filter, FrameMeta, Hub, Location, MediaType, X};
 
 /// Information about image transformation including source and destination dimensions.
 #[derive(Builder, Debug, Clone, Default)]
@@ -23,7 +24,7 @@ pub struct ImageTransformInfo {
 }
 
 /// Image resize modes for different scaling strategies.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum ResizeMode {
     /// StretchToFit
     FitExact,
@@ -34,12 +35,52 @@ pub enum ResizeMode {
     Letterbox,
 }
 
+/// Shape bucketing policy for dynamic-shape models.
+///
+/// Resized images are padded (bottom and right) up to a bucket, so that a dynamic-shape
+/// engine sees a small set of input shapes instead of a new one for every image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShapeBucket {
+    /// Round height and width up to multiples of the value.
+    Multiple(u32),
+    /// Use the smallest `(width, height)` that fits, falling back to the largest one.
+    Sizes(Vec<(u32, u32)>),
+}
+
+impl ShapeBucket {
+    /// Returns the bucket `(width, height)` for an image of size `(w, h)`.
+    ///
+    /// With [`ShapeBucket::Sizes`], the bucket may be smaller than the image when none fits.
+    pub fn fit(&self, w: u32, h: u32) -> (u32, u32) {
+        match self {
+            Self::Multiple(n) => {
+                let n = (*n).max(1);
+                (w.div_ceil(n) * n, h.div_ceil(n) * n)
+            }
+            Self::Sizes(sizes) => {
+                let area = |(w, h): &&(u32, u32)| *w as u64 * *h as u64;
+                sizes
+                    .iter()
+                    .filter(|(bw, bh)| *bw >= w && *bh >= h)
+                    .min_by_key(area)
+                    .or_else(|| sizes.iter().max_by_key(area))
+                    .copied()
+                    .unwrap_or((w, h))
+            }
+        }
+    }
+}
+
 /// Image wrapper with metadata and transformation capabilities.
 #[derive(Builder, Clone)]
 pub struct Image {
     image: RgbImage,
     source: Option<PathBuf>,
     media_type: MediaType,
+    /// Position in the full source listing, set by `DataLoader`.
+    index: Option<usize>,
+    /// Frame metadata of video and stream frames, set by `DataLoader`.
+    frame: Option<FrameMeta>,
 }
 
 impl Default for Image {
@@ -48,18 +89,26 @@ impl Default for Image {
             image: RgbImage::new(0, 0),
             source: None,
             media_type: MediaType::Unknown,
+            index: None,
+            frame: None,
         }
     }
 }
 
 impl std::fmt::Debug for Image {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Image")
-            .field("Height", &self.height())
+        let mut f = f.debug_struct("Image");
+        f.field("Height", &self.height())
             .field("Width", &self.width())
             .field("MediaType", &self.media_type)
-            .field("Source", &self.source)
-            .finish()
+            .field("Source", &self.source);
+        if let Some(index) = &self.index {
+            f.field("Index", index);
+        }
+        if let Some(frame) = &self.frame {
+            f.field("Frame", frame);
+        }
+        f.finish()
     }
 }
 
@@ -211,6 +260,8 @@ impl Image {
             image: image.to_rgb8(),
             media_type,
             source: Some(path),
+            index: None,
+            frame: None,
         })
     }
 
@@ -496,3 +547,26 @@ impl ImageVecExt for Vec<DynamicImage> {
         self.into_iter().map(|x| x.into()).collect()
     }
 }
+
+#[cfg(test)]
+mod tests_shape_bucket {
+    use super::ShapeBucket;
+
+    #[test]
+    fn test_multiple() {
+        let bucket = ShapeBucket::Multiple(32);
+        assert_eq!(bucket.fit(960, 540), (960, 544));
+        assert_eq!(bucket.fit(1, 33), (32, 64));
+        assert_eq!(ShapeBucket::Multiple(0).fit(7, 9), (7, 9));
+    }
+
+    #[test]
+    fn test_sizes() {
+        let bucket = ShapeBucket::Sizes(vec![(640, 640), (320, 320), (960, 480)]);
+        assert_eq!(bucket.fit(300, 200), (320, 320));
+        assert_eq!(bucket.fit(900, 300), (960, 480));
+        assert_eq!(bucket.fit(500, 600), (640, 640));
+        assert_eq!(bucket.fit(2000, 2000), (960, 480));
+        assert_eq!(ShapeBucket::Sizes(vec![]).fit(7, 9), (7, 9));
+    }
+}
@@ -0,0 +1,226 @@
+use once_cell::sync::Lazy;
+
+/// FP8 formats as defined by ONNX, stored one element per byte.
+///
+/// Conversion from `f32` rounds to the nearest representable value (ties to even) and
+/// saturates out-of-range values to the largest finite value, like ONNX `Cast` with `saturate=1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fp8 {
+    E4M3FN,
+    E4M3FNUZ,
+    E5M2,
+    E5M2FNUZ,
+}
+
+static TABLE_E4M3FN: Lazy<Vec<(f32, u8)>> = Lazy::new(|| Fp8::E4M3FN.build_table());
+static TABLE_E4M3FNUZ: Lazy<Vec<(f32, u8)>> = Lazy::new(|| Fp8::E4M3FNUZ.build_table());
+static TABLE_E5M2: Lazy<Vec<(f32, u8)>> = Lazy::new(|| Fp8::E5M2.build_table());
+static TABLE_E5M2FNUZ: Lazy<Vec<(f32, u8)>> = Lazy::new(|| Fp8::E5M2FNUZ.build_table());
+
+impl Fp8 {
+    fn man_bits(&self) -> u32 {
+        match self {
+            Self::E4M3FN | Self::E4M3FNUZ => 3,
+            Self::E5M2 | Self::E5M2FNUZ => 2,
+        }
+    }
+
+    fn bias(&self) -> i32 {
+        match self {
+            Self::E4M3FN => 7,
+            Self::E4M3FNUZ => 8,
+            Self::E5M2 => 15,
+            Self::E5M2FNUZ => 16,
+        }
+    }
+
+    fn is_fnuz(&self) -> bool {
+        matches!(self, Self::E4M3FNUZ | Self::E5M2FNUZ)
+    }
+
+    fn nan(&self) -> u8 {
+        if self.is_fnuz() {
+            0x80
+        } else {
+            0x7F
+        }
+    }
+
+    pub fn to_f32(&self, x: u8) -> f32 {
+        let (man_bits, exp_bits) = (self.man_bits(), 7 - self.man_bits());
+        if self.is_fnuz() && x == 0x80 {
+            return f32::NAN;
+        }
+        let sign = if x & 0x80 != 0 { -1. } else { 1. };
+        let exp = ((x >> man_bits) & ((1 << exp_bits) - 1)) as i32;
+        let man = (x & ((1 << man_bits) - 1)) as i32;
+        let exp_max = (1 << exp_bits) - 1;
+        match self {
+            Self::E4M3FN if exp == exp_max && man == (1 << man_bits) - 1 => return f32::NAN,
+            Self::E5M2 if exp == exp_max => {
+                return if man == 0 {
+                    sign * f32::INFINITY
+                } else {
+                    f32::NAN
+                }
+            }
+            _ => {}
+        }
+        let scale = (1 << man_bits) as f32;
+        if exp == 0 {
+            // subnormal
+            sign * (man as f32 / scale) * 2f32.powi(1 - self.bias())
+        } else {
+            sign * (1. + man as f32 / scale) * 2f32.powi(exp - self.bias())
+        }
+    }
+
+    pub fn from_f32(&self, x: f32) -> u8 {
+        if x.is_nan() {
+            return self.nan();
+        }
+        let sign = if x.is_sign_negative() { 0x80 } else { 0 };
+        let table = self.table();
+        let a = x.abs();
+        let code = if a.is_infinite() && *self == Self::E5M2 {
+            0x7C
+        } else if a >= table[table.len() - 1].0 {
+            table[table.len() - 1].1
+        } else {
+            // nearest, ties to even
+            let i = table.partition_point(|(v, _)| *v < a);
+            if i == 0 {
+                table[0].1
+            } else {
+                let (lo, hi) = (table[i - 1], table[i]);
+                let (d_lo, d_hi) = (a - lo.0, hi.0 - a);
+                if d_lo < d_hi || (d_lo == d_hi && lo.1 & 1 == 0) {
+                    lo.1
+                } else {
+                    hi.1
+                }
+            }
+        };
+
+        // no negative zero in FNUZ formats
+        if code == 0 && self.is_fnuz() {
+            0
+        } else {
+            code | sign
+        }
+    }
+
+    /// Non-negative finite values with their codes, in ascending order.
+    fn table(&self) -> &'static [(f32, u8)] {
+        match self {
+            Self::E4M3FN => &TABLE_E4M3FN,
+            Self::E4M3FNUZ => &TABLE_E4M3FNUZ,
+            Self::E5M2 => &TABLE_E5M2,
+            Self::E5M2FNUZ => &TABLE_E5M2FNUZ,
+        }
+    }
+
+    fn build_table(&self) -> Vec<(f32, u8)> {
+        (0..=0x7Fu8)
+            .map(|x| (self.to_f32(x), x))
+            .filter(|(v, _)| v.is_finite())
+            .collect()
+    }
+}
+
+/// Packs signed 4-bit integers two per byte, the first element in the low nibble.
+pub fn pack_int4(xs: &[i8]) -> Vec<u8> {
+    xs.chunks(2)
+        .map(|x| {
+            let lo = (x[0].clamp(-8, 7) as u8) & 0x0F;
+            let hi = x.get(1).map_or(0, |&x| (x.clamp(-8, 7) as u8) & 0x0F);
+            lo | (hi << 4)
+        })
+        .collect()
+}
+
+/// Packs unsigned 4-bit integers two per byte, the first element in the low nibble.
+pub fn pack_uint4(xs: &[u8]) -> Vec<u8> {
+    xs.chunks(2)
+        .map(|x| (x[0].min(15)) | (x.get(1).map_or(0, |&x| x.min(15)) << 4))
+        .collect()
+}
+
+/// Unpacks `n` signed 4-bit integers, sign-extending each nibble.
+pub fn unpack_int4(xs: &[u8], n: usize) -> Vec<i8> {
+    (0..n)
+        .map(|i| {
+            let x = (xs[i / 2] >> ((i % 2) * 4)) & 0x0F;
+            ((x << 4) as i8) >> 4
+        })
+        .collect()
+}
+
+/// Unpacks `n` unsigned 4-bit integers.
+pub fn unpack_uint4(xs: &[u8], n: usize) -> Vec<u8> {
+    (0..n)
+        .map(|i| (xs[i / 2] >> ((i % 2) * 4)) & 0x0F)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_low_bit {
+    use super::*;
+
+    #[test]
+    fn test_fp8_max() {
+        assert_eq!(Fp8::E4M3FN.to_f32(0x7E), 448.);
+        assert_eq!(Fp8::E4M3FNUZ.to_f32(0x7F), 240.);
+        assert_eq!(Fp8::E5M2.to_f32(0x7B), 57344.);
+        assert_eq!(Fp8::E5M2FNUZ.to_f32(0x7F), 57344.);
+    }
+
+    #[test]
+    fn test_fp8_specials() {
+        assert!(Fp8::E4M3FN.to_f32(0x7F).is_nan());
+        assert!(Fp8::E4M3FNUZ.to_f32(0x80).is_nan());
+        assert_eq!(Fp8::E5M2.to_f32(0xFC), f32::NEG_INFINITY);
+        assert_eq!(Fp8::E4M3FN.from_f32(f32::NAN), 0x7F);
+        assert_eq!(Fp8::E5M2FNUZ.from_f32(f32::NAN), 0x80);
+        assert_eq!(Fp8::E4M3FN.from_f32(1e6), 0x7E);
+        assert_eq!(Fp8::E4M3FN.from_f32(-1e6), 0xFE);
+        assert_eq!(Fp8::E5M2.from_f32(f32::INFINITY), 0x7C);
+        assert_eq!(Fp8::E4M3FNUZ.from_f32(-0.), 0);
+        assert_eq!(Fp8::E4M3FN.from_f32(-0.), 0x80);
+    }
+
+    #[test]
+    fn test_fp8_roundtrip() {
+        for fp8 in [Fp8::E4M3FN, Fp8::E4M3FNUZ, Fp8::E5M2, Fp8::E5M2FNUZ] {
+            for x in 0..=255u8 {
+                let y = fp8.to_f32(x);
+                if y.is_finite() && !(fp8.is_fnuz() && x == 0x80) {
+                    assert_eq!(fp8.from_f32(y), x, "{:?}: {:#04x} -> {}", fp8, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fp8_rounding() {
+        // 1.0 = 0x38, 1.125 = 0x39, 1.25 = 0x3A in E4M3FN
+        assert_eq!(Fp8::E4M3FN.from_f32(1.06), 0x38);
+        assert_eq!(Fp8::E4M3FN.from_f32(1.0625), 0x38); // tie, to even
+        assert_eq!(Fp8::E4M3FN.from_f32(1.1875), 0x3A); // tie, to even
+        assert_eq!(Fp8::E4M3FN.from_f32(1.1), 0x39);
+    }
+
+    #[test]
+    fn test_int4() {
+        let xs = [-8, -1, 0, 7, 3];
+        let packed = pack_int4(&xs);
+        assert_eq!(packed.len(), 3);
+        assert_eq!(unpack_int4(&packed, xs.len()), xs);
+        assert_eq!(unpack_int4(&pack_int4(&[-20, 20]), 2), [-8, 7]);
+
+        let xs = [0, 15, 9];
+        let packed = pack_uint4(&xs);
+        assert_eq!(packed, [0xF0, 0x09]);
+        assert_eq!(unpack_uint4(&packed, xs.len()), xs);
+    }
+}
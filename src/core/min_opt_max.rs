@@ -1,7 +1,9 @@
 use aksr::Builder;
+use serde::{Deserialize, Serialize};
 
 /// A value composed of Min-Opt-Max
-#[derive(Builder, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Builder, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MinOptMax {
     #[args(except(setter))]
     min: usize,
@@ -2,6 +2,7 @@
 mod ort_config;
 #[macro_use]
 mod processor_config;
+mod cancel;
 mod config;
 mod dataloader;
 mod device;
@@ -10,20 +11,32 @@ mod dtype;
 mod dynconf;
 #[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
 mod engine;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+mod engine_pool;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+mod fp16_converter;
+mod frame_meta;
 pub mod global_ts;
 mod hardware_config;
 mod hub;
 mod iiix;
 pub mod image;
 mod logits_sampler;
+mod low_bit;
 mod media;
 mod min_opt_max;
+mod model_bytes;
 mod names;
 #[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
 #[allow(clippy::all)]
 pub(crate) mod onnx;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+mod onnx_inspector;
 mod ops;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+mod parity;
 pub mod perf;
+mod pool;
 mod processor;
 mod retry;
 mod scale;
@@ -33,8 +46,11 @@ pub mod ts;
 mod utils;
 mod version;
 mod x;
+mod x_any;
 mod xs;
+mod xs_any;
 
+pub use cancel::*;
 pub use config::*;
 pub use dataloader::*;
 pub use device::Device;
@@ -43,6 +59,11 @@ pub use dtype::DType;
 pub use dynconf::DynConf;
 #[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
 pub use engine::*;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+pub use engine_pool::*;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+pub use fp16_converter::*;
+pub use frame_meta::*;
 pub use perf::*;
 // Macros are exported at crate root via #[macro_export]
 pub use global_ts::*;
@@ -51,11 +72,18 @@ pub use hub::*;
 pub(crate) use iiix::Iiix;
 pub use image::*;
 pub use logits_sampler::LogitsSampler;
+pub use low_bit::*;
 pub use media::*;
 pub use min_opt_max::MinOptMax;
+pub use model_bytes::*;
 pub use names::*;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+pub use onnx_inspector::*;
 pub use ops::*;
+#[cfg(any(feature = "ort-download-binaries", feature = "ort-load-dynamic"))]
+pub use parity::*;
 pub use ort_config::ORTConfig;
+pub use pool::*;
 pub use processor::*;
 pub use processor_config::ProcessorConfig;
 pub use scale::Scale;
@@ -65,4 +93,6 @@ pub use ts::Ts;
 pub use utils::*;
 pub use version::Version;
 pub use x::X;
+pub use x_any::XAny;
 pub use xs::Xs;
+pub use xs_any::XAnys;
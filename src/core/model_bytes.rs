@@ -0,0 +1,74 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+/// ONNX model held in memory, cheap to clone.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ModelBytes(Arc<[u8]>);
+
+impl std::fmt::Debug for ModelBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ModelBytes({} bytes)", self.0.len())
+    }
+}
+
+impl std::ops::Deref for ModelBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ModelBytes {
+    fn from(x: Vec<u8>) -> Self {
+        Self(x.into())
+    }
+}
+
+impl From<&[u8]> for ModelBytes {
+    fn from(x: &[u8]) -> Self {
+        Self(x.into())
+    }
+}
+
+type DecryptFn = dyn Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync;
+
+/// Hook that turns the contents of an encrypted model file into plaintext ONNX bytes.
+///
+/// The decrypted model is only kept in memory and never written to disk.
+#[derive(Clone)]
+pub struct Decryptor(Arc<DecryptFn>);
+
+impl std::fmt::Debug for Decryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Decryptor")
+    }
+}
+
+impl Decryptor {
+    pub fn new(f: impl Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn decrypt(&self, x: &[u8]) -> Result<ModelBytes> {
+        (self.0)(x).map(ModelBytes::from)
+    }
+
+    /// Reads and decrypts a model file.
+    pub fn try_read<P: AsRef<std::path::Path>>(&self, p: P) -> Result<ModelBytes> {
+        let x = std::fs::read(p.as_ref()).map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to read the encrypted model: {}. {}",
+                p.as_ref().display(),
+                err
+            )
+        })?;
+        self.decrypt(&x).map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to decrypt the model: {}. {}",
+                p.as_ref().display(),
+                err
+            )
+        })
+    }
+}
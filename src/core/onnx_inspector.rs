@@ -0,0 +1,570 @@
+use anyhow::Result;
+use prost::Message;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::{
+    onnx::{self, tensor_shape_proto::dimension, type_proto},
+    Engine,
+};
+
+/// Op-type histogram, opsets and compute estimate of an ONNX graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OnnxSummary {
+    pub ir_version: i64,
+    /// Opset version per domain, the default domain shown as `ai.onnx`.
+    pub opsets: Vec<(String, i64)>,
+    /// Number of nodes per op type, by descending count.
+    pub ops: Vec<(String, usize)>,
+    pub num_nodes: usize,
+    /// Number of elements in all initializers.
+    pub num_params: usize,
+    /// Multiply-accumulates of Conv, MatMul and Gemm nodes, per op type.
+    pub macs: Vec<(String, u64)>,
+    /// Conv, MatMul and Gemm nodes whose shapes could not be resolved.
+    pub unresolved: Vec<String>,
+}
+
+impl OnnxSummary {
+    pub fn total_macs(&self) -> u64 {
+        self.macs.iter().map(|(_, x)| x).sum()
+    }
+
+    /// Approximate FLOPs, counting one MAC as two operations.
+    pub fn total_flops(&self) -> u64 {
+        self.total_macs() * 2
+    }
+}
+
+impl std::fmt::Display for OnnxSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let opsets: Vec<String> = self
+            .opsets
+            .iter()
+            .map(|(domain, version)| format!("{}={}", domain, version))
+            .collect();
+        writeln!(
+            f,
+            "IR Version: {}, Opsets: [{}], Nodes: {}, Parameters: {}, MACs: {}, FLOPs: {}",
+            self.ir_version,
+            opsets.join(", "),
+            self.num_nodes,
+            human_count(self.num_params as u64),
+            human_count(self.total_macs()),
+            human_count(self.total_flops()),
+        )?;
+        for (op, n) in self.ops.iter() {
+            let macs = self
+                .macs
+                .iter()
+                .find(|(x, _)| x == op)
+                .map_or(String::new(), |(_, x)| {
+                    format!(" ({} MACs)", human_count(*x))
+                });
+            writeln!(f, "    {:<24}{:>6}{}", op, n, macs)?;
+        }
+        if !self.unresolved.is_empty() {
+            write!(
+                f,
+                "    MACs not counted for {} nodes with unknown shapes",
+                self.unresolved.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Inspects ONNX models and exposes intermediate tensors as extra outputs.
+///
+/// Shapes come from the graph inputs, outputs, initializers and `value_info`, so MACs are
+/// only counted for nodes whose shapes are recorded there. Symbolic dims count as 1.
+#[derive(Debug, Clone)]
+pub struct OnnxInspector {
+    proto: onnx::ModelProto,
+}
+
+impl OnnxInspector {
+    pub fn try_from_file<P: AsRef<Path>>(p: P) -> Result<Self> {
+        Ok(Self {
+            proto: Engine::load_onnx(p)?,
+        })
+    }
+
+    pub fn try_from_bytes(x: &[u8]) -> Result<Self> {
+        Ok(Self {
+            proto: Engine::load_onnx_from_bytes(x)?,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_proto(proto: onnx::ModelProto) -> Self {
+        Self { proto }
+    }
+
+    pub fn summary(&self) -> OnnxSummary {
+        Self::summarize(&self.proto)
+    }
+
+    /// Names of all tensors produced by nodes, in graph order.
+    pub fn tensor_names(&self) -> Vec<String> {
+        self.graph()
+            .map(|g| {
+                g.node
+                    .iter()
+                    .flat_map(|x| x.output.iter())
+                    .filter(|x| !x.is_empty())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns a copy of the model with the given intermediate tensors added to its outputs.
+    pub fn with_outputs(&self, names: &[&str]) -> Result<Self> {
+        let mut proto = self.proto.clone();
+        let graph = match proto.graph.as_mut() {
+            Some(graph) => graph,
+            None => anyhow::bail!("Failed to expose outputs. No graph found."),
+        };
+        let produced: HashSet<&str> = graph
+            .node
+            .iter()
+            .flat_map(|x| x.output.iter().map(|x| x.as_str()))
+            .collect();
+        let mut outputs = Vec::new();
+        for &name in names {
+            if !produced.contains(name) {
+                anyhow::bail!(
+                    "Failed to expose `{}`. No node in the graph produces it.",
+                    name
+                );
+            }
+            if graph.output.iter().any(|x| x.name == name) {
+                continue;
+            }
+            // ORT infers the type of outputs declared by name only
+            let output = graph
+                .value_info
+                .iter()
+                .find(|x| x.name == name)
+                .cloned()
+                .unwrap_or_else(|| onnx::ValueInfoProto {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+            outputs.push(output);
+        }
+        graph.output.extend(outputs);
+
+        Ok(Self { proto })
+    }
+
+    /// Serialized model, e.g. for [`crate::ORTConfig::with_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.proto.encode_to_vec()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, p: P) -> Result<()> {
+        std::fs::write(p.as_ref(), self.to_bytes()).map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to write the ONNX model: {}. {}",
+                p.as_ref().display(),
+                err
+            )
+        })
+    }
+
+    fn graph(&self) -> Option<&onnx::GraphProto> {
+        self.proto.graph.as_ref()
+    }
+
+    pub(crate) fn summarize(proto: &onnx::ModelProto) -> OnnxSummary {
+        let mut summary = OnnxSummary {
+            ir_version: proto.ir_version,
+            opsets: proto
+                .opset_import
+                .iter()
+                .map(|x| {
+                    let domain = match x.domain.as_str() {
+                        "" => "ai.onnx".to_string(),
+                        domain => domain.to_string(),
+                    };
+                    (domain, x.version)
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let graph = match proto.graph.as_ref() {
+            Some(graph) => graph,
+            None => return summary,
+        };
+
+        let mut ops: HashMap<&str, usize> = HashMap::new();
+        for node in graph.node.iter() {
+            *ops.entry(node.op_type.as_str()).or_default() += 1;
+        }
+        let mut ops: Vec<(String, usize)> =
+            ops.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        ops.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        summary.ops = ops;
+        summary.num_nodes = graph.node.len();
+        summary.num_params = graph
+            .initializer
+            .iter()
+            .map(|x| x.dims.iter().product::<i64>().max(0) as usize)
+            .sum();
+
+        let shapes = Self::shapes(graph);
+        let mut macs: Vec<(String, u64)> = Vec::new();
+        for node in graph.node.iter() {
+            let x = match node.op_type.as_str() {
+                "Conv" => Self::conv_macs(node, &shapes),
+                "MatMul" => Self::matmul_macs(node, &shapes),
+                "Gemm" => Self::gemm_macs(node, &shapes),
+                _ => continue,
+            };
+            match x {
+                Some(x) => match macs.iter_mut().find(|(op, _)| *op == node.op_type) {
+                    Some((_, n)) => *n += x,
+                    None => macs.push((node.op_type.clone(), x)),
+                },
+                None => summary.unresolved.push(if node.name.is_empty() {
+                    node.output.first().cloned().unwrap_or_default()
+                } else {
+                    node.name.clone()
+                }),
+            }
+        }
+        summary.macs = macs;
+
+        summary
+    }
+
+    /// Known shapes, with symbolic dims as `None`.
+    fn shapes(graph: &onnx::GraphProto) -> HashMap<&str, Vec<Option<u64>>> {
+        let mut shapes = HashMap::new();
+        for x in graph
+            .input
+            .iter()
+            .chain(graph.output.iter())
+            .chain(graph.value_info.iter())
+        {
+            if let Some(type_proto::Value::TensorType(t)) =
+                x.r#type.as_ref().and_then(|t| t.value.as_ref())
+            {
+                if let Some(shape) = t.shape.as_ref() {
+                    let dims = shape
+                        .dim
+                        .iter()
+                        .map(|d| match d.value {
+                            Some(dimension::Value::DimValue(x)) if x > 0 => Some(x as u64),
+                            _ => None,
+                        })
+                        .collect();
+                    shapes.insert(x.name.as_str(), dims);
+                }
+            }
+        }
+        for x in graph.initializer.iter() {
+            shapes.insert(
+                x.name.as_str(),
+                x.dims.iter().map(|&d| Some(d.max(0) as u64)).collect(),
+            );
+        }
+
+        shapes
+    }
+
+    fn conv_macs(node: &onnx::NodeProto, shapes: &HashMap<&str, Vec<Option<u64>>>) -> Option<u64> {
+        // W: [C_out, C_in / group, k...]
+        let w = shapes.get(node.input.get(1)?.as_str())?;
+        let w: Vec<u64> = w.iter().map(|x| x.unwrap_or(1)).collect();
+        if w.len() < 3 {
+            return None;
+        }
+        let spatial = match shapes.get(node.output.first()?.as_str()) {
+            Some(y) if y.len() == w.len() => y.clone(),
+            _ => Self::conv_output_shape(node, shapes.get(node.input.first()?.as_str())?, &w)?,
+        };
+        let n = spatial[0].unwrap_or(1);
+        let out: u64 = spatial[2..].iter().map(|x| x.unwrap_or(1)).product();
+
+        Some(n * w[0] * out * w[1..].iter().product::<u64>())
+    }
+
+    fn conv_output_shape(
+        node: &onnx::NodeProto,
+        x: &[Option<u64>],
+        w: &[u64],
+    ) -> Option<Vec<Option<u64>>> {
+        if x.len() != w.len() {
+            return None;
+        }
+        let k = &w[2..];
+        let n_spatial = k.len();
+        let ints = |name: &str, default: i64| -> Vec<i64> {
+            node.attribute
+                .iter()
+                .find(|a| a.name == name)
+                .map_or(vec![default; n_spatial], |a| a.ints.clone())
+        };
+        let (strides, dilations, pads) =
+            (ints("strides", 1), ints("dilations", 1), ints("pads", 0));
+        let auto_pad = node
+            .attribute
+            .iter()
+            .find(|a| a.name == "auto_pad")
+            .map(|a| String::from_utf8_lossy(&a.s).to_string())
+            .unwrap_or_default();
+
+        let mut y = vec![x[0], Some(w[0])];
+        for i in 0..n_spatial {
+            let d = x[2 + i].map(|d| d as i64);
+            let stride = *strides.get(i)?;
+            y.push(d.map(|d| match auto_pad.as_str() {
+                "SAME_UPPER" | "SAME_LOWER" => ((d + stride - 1) / stride) as u64,
+                _ => {
+                    let pad = pads.get(i).unwrap_or(&0) + pads.get(i + n_spatial).unwrap_or(&0);
+                    let span = dilations.get(i).unwrap_or(&1) * (k[i] as i64 - 1) + 1;
+                    ((d + pad - span) / stride + 1).max(0) as u64
+                }
+            }));
+        }
+
+        Some(y)
+    }
+
+    fn matmul_macs(
+        node: &onnx::NodeProto,
+        shapes: &HashMap<&str, Vec<Option<u64>>>,
+    ) -> Option<u64> {
+        let a = shapes.get(node.input.first()?.as_str())?;
+        let b = shapes.get(node.input.get(1)?.as_str())?;
+        let a: Vec<u64> = a.iter().map(|x| x.unwrap_or(1)).collect();
+        let b: Vec<u64> = b.iter().map(|x| x.unwrap_or(1)).collect();
+        if a.is_empty() || b.is_empty() {
+            return None;
+        }
+        let (m, k) = match a.len() {
+            1 => (1, a[0]),
+            n => (a[n - 2], a[n - 1]),
+        };
+        let n = match b.len() {
+            1 => 1,
+            n => b[n - 1],
+        };
+        // broadcast batch dims
+        let (a_batch, b_batch) = (
+            &a[..a.len().saturating_sub(2)],
+            &b[..b.len().saturating_sub(2)],
+        );
+        let rank = a_batch.len().max(b_batch.len());
+        let batch: u64 = (0..rank)
+            .map(|i| {
+                let da = a_batch
+                    .len()
+                    .checked_sub(rank - i)
+                    .map_or(1, |j| a_batch[j]);
+                let db = b_batch
+                    .len()
+                    .checked_sub(rank - i)
+                    .map_or(1, |j| b_batch[j]);
+                da.max(db)
+            })
+            .product();
+
+        Some(batch * m * k * n)
+    }
+
+    fn gemm_macs(node: &onnx::NodeProto, shapes: &HashMap<&str, Vec<Option<u64>>>) -> Option<u64> {
+        let a = shapes.get(node.input.first()?.as_str())?;
+        let b = shapes.get(node.input.get(1)?.as_str())?;
+        if a.len() != 2 || b.len() != 2 {
+            return None;
+        }
+        let trans = |name: &str| node.attribute.iter().any(|x| x.name == name && x.i != 0);
+        let (m, k) = match trans("transA") {
+            true => (a[1], a[0]),
+            false => (a[0], a[1]),
+        };
+        let n = match trans("transB") {
+            true => b[0],
+            false => b[1],
+        };
+
+        Some(m.unwrap_or(1) * k.unwrap_or(1) * n.unwrap_or(1))
+    }
+}
+
+fn human_count(x: u64) -> String {
+    const UNITS: [&str; 5] = ["", "K", "M", "G", "T"];
+    let mut x = x as f64;
+    let mut i = 0;
+    while x >= 1000. && i < UNITS.len() - 1 {
+        x /= 1000.;
+        i += 1;
+    }
+    match i {
+        0 => format!("{}", x),
+        _ => format!("{:.2}{}", x, UNITS[i]),
+    }
+}
+
+#[cfg(test)]
+mod tests_onnx_inspector {
+    use super::*;
+    use crate::onnx::{tensor_proto::DataType, tensor_shape_proto};
+
+    fn value_info(name: &str, dims: &[i64]) -> onnx::ValueInfoProto {
+        onnx::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(onnx::TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: DataType::Float as i32,
+                    shape: Some(onnx::TensorShapeProto {
+                        dim: dims
+                            .iter()
+                            .map(|&d| tensor_shape_proto::Dimension {
+                                value: Some(match d {
+                                    -1 => dimension::Value::DimParam("batch".to_string()),
+                                    d => dimension::Value::DimValue(d),
+                                }),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn initializer(name: &str, dims: &[i64]) -> onnx::TensorProto {
+        onnx::TensorProto {
+            name: name.to_string(),
+            dims: dims.to_vec(),
+            data_type: DataType::Float as i32,
+            ..Default::default()
+        }
+    }
+
+    fn node(
+        op_type: &str,
+        input: &[&str],
+        output: &[&str],
+        attribute: Vec<onnx::AttributeProto>,
+    ) -> onnx::NodeProto {
+        onnx::NodeProto {
+            input: input.iter().map(|x| x.to_string()).collect(),
+            output: output.iter().map(|x| x.to_string()).collect(),
+            op_type: op_type.to_string(),
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    fn ints(name: &str, ints: &[i64]) -> onnx::AttributeProto {
+        onnx::AttributeProto {
+            name: name.to_string(),
+            ints: ints.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    // x[-1, 3, 32, 32] -> Conv(8, 3x3, s2, p1) -> Relu -> Flatten -> Gemm(10) -> y
+    fn inspector() -> OnnxInspector {
+        OnnxInspector::from_proto(onnx::ModelProto {
+            ir_version: 8,
+            opset_import: vec![
+                onnx::OperatorSetIdProto {
+                    domain: String::new(),
+                    version: 17,
+                },
+                onnx::OperatorSetIdProto {
+                    domain: "com.microsoft".to_string(),
+                    version: 1,
+                },
+            ],
+            graph: Some(onnx::GraphProto {
+                node: vec![
+                    node(
+                        "Conv",
+                        &["x", "w", "b"],
+                        &["conv"],
+                        vec![ints("strides", &[2, 2]), ints("pads", &[1, 1, 1, 1])],
+                    ),
+                    node("Relu", &["conv"], &["relu"], vec![]),
+                    node("Flatten", &["relu"], &["flat"], vec![]),
+                    node("Gemm", &["flat", "fc"], &["y"], vec![]),
+                ],
+                initializer: vec![
+                    initializer("w", &[8, 3, 3, 3]),
+                    initializer("b", &[8]),
+                    initializer("fc", &[2048, 10]),
+                ],
+                input: vec![value_info("x", &[-1, 3, 32, 32])],
+                output: vec![value_info("y", &[-1, 10])],
+                value_info: vec![value_info("flat", &[-1, 2048])],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = inspector().summary();
+        assert_eq!(
+            summary.opsets,
+            vec![
+                ("ai.onnx".to_string(), 17),
+                ("com.microsoft".to_string(), 1)
+            ]
+        );
+        assert_eq!(summary.num_nodes, 4);
+        assert_eq!(summary.ops[0], ("Conv".to_string(), 1));
+        assert_eq!(summary.num_params, 8 * 27 + 8 + 2048 * 10);
+        assert_eq!(
+            summary.macs,
+            vec![
+                ("Conv".to_string(), 8 * 16 * 16 * 27),
+                ("Gemm".to_string(), 2048 * 10),
+            ]
+        );
+        assert!(summary.unresolved.is_empty());
+        assert_eq!(summary.total_flops(), 2 * (8 * 16 * 16 * 27 + 2048 * 10));
+    }
+
+    #[test]
+    fn test_matmul_broadcast() {
+        let shapes = HashMap::from([
+            ("a", vec![Some(2), None, Some(4), Some(8)]),
+            ("b", vec![Some(8), Some(16)]),
+        ]);
+        let node = node("MatMul", &["a", "b"], &["y"], vec![]);
+        assert_eq!(
+            OnnxInspector::matmul_macs(&node, &shapes),
+            Some(2 * 4 * 8 * 16)
+        );
+    }
+
+    #[test]
+    fn test_with_outputs() {
+        let inspector = inspector();
+        assert_eq!(inspector.tensor_names(), ["conv", "relu", "flat", "y"]);
+        assert!(inspector.with_outputs(&["missing"]).is_err());
+
+        let exposed = inspector.with_outputs(&["relu", "flat", "y"]).unwrap();
+        let outputs = &exposed.graph().unwrap().output;
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(
+            outputs[1],
+            onnx::ValueInfoProto {
+                name: "relu".to_string(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(outputs[2], value_info("flat", &[-1, 2048]));
+    }
+}
@@ -360,14 +360,14 @@ impl Ops<'_> {
     }
 
     pub fn build_resizer_filter(ty: &str) -> Result<(Resizer, ResizeOptions)> {
-        let ty = match ty {
-            "Box" => FilterType::Box,
-            "Bilinear" => FilterType::Bilinear,
-            "Hamming" => FilterType::Hamming,
-            "CatmullRom" => FilterType::CatmullRom,
-            "Mitchell" => FilterType::Mitchell,
-            "Gaussian" => FilterType::Gaussian,
-            "Lanczos3" => FilterType::Lanczos3,
+        let ty = match ty.to_ascii_lowercase().as_str() {
+            "box" => FilterType::Box,
+            "bilinear" => FilterType::Bilinear,
+            "hamming" => FilterType::Hamming,
+            "catmullrom" => FilterType::CatmullRom,
+            "mitchell" => FilterType::Mitchell,
+            "gaussian" => FilterType::Gaussian,
+            "lanczos3" => FilterType::Lanczos3,
             _ => anyhow::bail!("Unsupported resizer's filter type: {ty}"),
         };
         Ok((
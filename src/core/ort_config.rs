@@ -1,21 +1,36 @@
 use aksr::Builder;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
-use crate::{try_fetch_file_stem, DType, Device, HardwareConfig, Hub, Iiix, MinOptMax};
+use crate::{
+    try_fetch_file_stem, DType, Decryptor, Device, HardwareConfig, Hub, Iiix, MinOptMax, ModelBytes,
+};
 
 /// ONNX Runtime configuration with device and optimization settings.
-#[derive(Builder, Debug, Clone)]
+#[derive(Builder, Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ORTConfig {
     pub file: String,
+    #[args(except(setter))]
+    #[serde(skip)]
+    pub bytes: Option<ModelBytes>, // Loaded from memory instead of `file`
+    #[args(except(setter))]
+    #[serde(skip)]
+    pub decryptor: Option<Decryptor>, // Applied to `file` before loading
     pub device: Device,
+    pub devices: Vec<Device>, // Ordered fallback chain, tried before giving up
     pub iiixs: Vec<Iiix>,
     pub num_dry_run: usize,
+    pub io_binding: bool,
     pub spec: String, // TODO: move out
     pub dtype: DType, // For dynamically loading the model
     // global
     pub graph_opt_level: Option<u8>,
     pub num_intra_threads: Option<usize>,
     pub num_inter_threads: Option<usize>,
+    pub operator_libraries: Vec<String>, // Custom operator shared libraries
+    pub graph_cache: Option<String>, // Directory of optimized graphs, saved once and loaded later
+    pub profiling: bool,             // ORT session profiling, merged into `Perf` per `spec`
     // hardware configurations
     pub hardware: HardwareConfig,
 }
@@ -24,21 +39,43 @@ impl Default for ORTConfig {
     fn default() -> Self {
         Self {
             file: Default::default(),
+            bytes: None,
+            decryptor: None,
             device: Default::default(),
+            devices: Default::default(),
             iiixs: Default::default(),
             spec: Default::default(),
             dtype: Default::default(),
             num_dry_run: 3,
+            io_binding: false,
             graph_opt_level: Default::default(),
             num_intra_threads: None,
             num_inter_threads: None,
+            operator_libraries: vec![],
+            graph_cache: None,
+            profiling: false,
             hardware: HardwareConfig::default(),
         }
     }
 }
 
 impl ORTConfig {
+    /// Whether nothing identifies or shapes this engine yet, i.e. it's not used by the model.
+    pub(crate) fn is_unset(&self) -> bool {
+        self.file.is_empty() && self.bytes.is_none() && self.iiixs.is_empty()
+    }
+
     pub fn try_commit(mut self, name: &str) -> Result<Self> {
+        // In-memory model
+        if self.bytes.is_some() {
+            let stem = match self.file.is_empty() {
+                true => "memory".to_string(),
+                false => try_fetch_file_stem(&self.file)?,
+            };
+            self.spec = format!("{}/{}", name, stem);
+            return Ok(self);
+        }
+
         // Identify the local model or fetch the remote model
         if std::path::PathBuf::from(&self.file).exists() {
             // Local
@@ -55,7 +92,7 @@ impl ORTConfig {
                 Some((owner, repo, tag, _file_name)) => {
                     let stem = try_fetch_file_stem(&self.file)?;
                     self.spec = format!("{}/{}-{}-{}-{}", name, owner, repo, tag, stem);
-                    self.file = Hub::default().try_fetch(&self.file)?;
+                    self.file = Hub::default().try_fetch_with_sidecars(&self.file)?;
                 }
                 None => {
                     // append dtype to model file
@@ -78,20 +115,8 @@ impl ORTConfig {
 
                     let stem = try_fetch_file_stem(&self.file)?;
                     self.spec = format!("{}/{}", name, stem);
-                    self.file = Hub::default().try_fetch(&format!("{}/{}", name, self.file))?;
-
-                    // try fetch external data file if it exists
-                    match Hub::default().try_fetch(&format!("{}_data", self.file)) {
-                        Ok(external_data_file) => {
-                            log::debug!(
-                                "Successfully fetched external data file: {}",
-                                external_data_file
-                            );
-                        }
-                        Err(_) => {
-                            log::debug!("No external data file found for model {}", self.file);
-                        }
-                    }
+                    self.file = Hub::default()
+                        .try_fetch_with_sidecars(&format!("{}/{}", name, self.file))?;
                 }
             }
         }
@@ -101,6 +126,21 @@ impl ORTConfig {
 }
 
 impl ORTConfig {
+    /// Loads the model from an in-memory buffer instead of a file.
+    pub fn with_bytes(mut self, x: impl Into<ModelBytes>) -> Self {
+        self.bytes = Some(x.into());
+        self
+    }
+
+    /// Decrypts the model file into memory before loading it.
+    pub fn with_decryptor(
+        mut self,
+        f: impl Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        self.decryptor = Some(Decryptor::new(f));
+        self
+    }
+
     pub fn with_ixx(mut self, i: usize, ii: usize, x: MinOptMax) -> Self {
         self.iiixs.push(Iiix::from((i, ii, x)));
         self
@@ -111,6 +151,12 @@ impl ORTConfig {
         self
     }
 
+    /// Registers a custom operator shared library, e.g. onnxruntime-extensions.
+    pub fn with_operator_library(mut self, x: &str) -> Self {
+        self.operator_libraries.push(x.to_string());
+        self
+    }
+
     // Hardware configuration methods
     pub fn with_cpu_arena_allocator(mut self, x: bool) -> Self {
         self.hardware.cpu.arena_allocator = x;
@@ -226,6 +272,17 @@ macro_rules! impl_ort_config_methods {
                     self.$field = self.$field.with_file(file);
                     self
                 }
+                pub fn [<with_ $field _bytes>](mut self, x: impl Into<$crate::ModelBytes>) -> Self {
+                    self.$field = self.$field.with_bytes(x);
+                    self
+                }
+                pub fn [<with_ $field _decryptor>](
+                    mut self,
+                    f: impl Fn(&[u8]) -> anyhow::Result<Vec<u8>> + Send + Sync + 'static,
+                ) -> Self {
+                    self.$field = self.$field.with_decryptor(f);
+                    self
+                }
                 pub fn [<with_ $field _dtype>](mut self, dtype: $crate::DType) -> Self {
                     self.$field = self.$field.with_dtype(dtype);
                     self
@@ -234,10 +291,18 @@ macro_rules! impl_ort_config_methods {
                     self.$field = self.$field.with_device(device);
                     self
                 }
+                pub fn [<with_ $field _devices>](mut self, devices: &[$crate::Device]) -> Self {
+                    self.$field = self.$field.with_devices(devices);
+                    self
+                }
                 pub fn [<with_ $field _num_dry_run>](mut self, x: usize) -> Self {
                     self.$field = self.$field.with_num_dry_run(x);
                     self
                 }
+                pub fn [<with_ $field _io_binding>](mut self, x: bool) -> Self {
+                    self.$field = self.$field.with_io_binding(x);
+                    self
+                }
                 pub fn [<with_ $field _ixx>](mut self, i: usize, ii: usize, x: $crate::MinOptMax) -> Self {
                     self.$field = self.$field.with_ixx(i, ii, x);
                     self
@@ -255,6 +320,18 @@ macro_rules! impl_ort_config_methods {
                     self.$field = self.$field.with_num_inter_threads(x);
                     self
                 }
+                pub fn [<with_ $field _operator_library>](mut self, x: &str) -> Self {
+                    self.$field = self.$field.with_operator_library(x);
+                    self
+                }
+                pub fn [<with_ $field _graph_cache>](mut self, x: &str) -> Self {
+                    self.$field = self.$field.with_graph_cache(x);
+                    self
+                }
+                pub fn [<with_ $field _profiling>](mut self, x: bool) -> Self {
+                    self.$field = self.$field.with_profiling(x);
+                    self
+                }
                 // hardware configuration methods - delegate to the field's hardware methods
                 pub fn [<with_ $field _cpu_arena_allocator>](mut self, x: bool) -> Self {
                     self.$field = self.$field.with_cpu_arena_allocator(x);
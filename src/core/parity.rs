@@ -0,0 +1,247 @@
+use aksr::Builder;
+use anyhow::Result;
+use log::warn;
+use ndarray::{ArrayD, Dimension};
+use std::path::{Path, PathBuf};
+
+use crate::{Engine, ORTConfig, XAny, XAnys};
+
+/// Numerical parity check of `Engine` outputs against reference `.npy` files.
+///
+/// A case directory holds `inputs/<name>.npy` for every model input and
+/// `outputs/<name>.npy` for the outputs to compare, keyed by the ONNX tensor names
+/// (`/` and `:` replaced by `_`). Elements match when `|y - y_ref| <= atol + rtol * |y_ref|`.
+#[derive(Builder, Debug, Clone)]
+pub struct Parity {
+    /// Absolute tolerance.
+    pub atol: f32,
+    /// Relative tolerance.
+    pub rtol: f32,
+    /// Number of worst elements reported per output.
+    pub topk: usize,
+}
+
+impl Default for Parity {
+    fn default() -> Self {
+        Self {
+            atol: 1e-4,
+            rtol: 1e-3,
+            topk: 5,
+        }
+    }
+}
+
+/// Comparison of one output with its reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParityReport {
+    /// Output name.
+    pub name: String,
+    /// Maximum absolute error.
+    pub max_abs_err: f32,
+    /// Mean absolute error.
+    pub mean_abs_err: f32,
+    /// Maximum relative error.
+    pub max_rel_err: f32,
+    /// Number of elements out of tolerance.
+    pub num_mismatched: usize,
+    /// Number of compared elements.
+    pub num_elements: usize,
+    /// Worst elements as `(index, value, reference)`, by descending absolute error.
+    pub worst: Vec<(Vec<usize>, f32, f32)>,
+}
+
+impl ParityReport {
+    pub fn passed(&self) -> bool {
+        self.num_mismatched == 0
+    }
+}
+
+impl std::fmt::Display for ParityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: max_abs_err={:.6e}, mean_abs_err={:.6e}, max_rel_err={:.6e}, mismatched={}/{}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.name,
+            self.max_abs_err,
+            self.mean_abs_err,
+            self.max_rel_err,
+            self.num_mismatched,
+            self.num_elements,
+        )?;
+        for (index, y, y_ref) in self.worst.iter() {
+            write!(f, "\n    at {:?}: {} vs {}", index, y, y_ref)?;
+        }
+        Ok(())
+    }
+}
+
+impl Parity {
+    /// Compares `y` with the reference `y_ref` element-wise.
+    pub fn compare(
+        &self,
+        name: &str,
+        y: &ArrayD<f32>,
+        y_ref: &ArrayD<f32>,
+    ) -> Result<ParityReport> {
+        if y.shape() != y_ref.shape() {
+            anyhow::bail!(
+                "Output `{}` has shape {:?}, but the reference has shape {:?}",
+                name,
+                y.shape(),
+                y_ref.shape()
+            );
+        }
+
+        let (mut max_abs_err, mut sum_abs_err, mut max_rel_err) = (0f32, 0f64, 0f32);
+        let mut num_mismatched = 0;
+        let mut errs: Vec<(f32, Vec<usize>, f32, f32)> = Vec::new();
+        for ((index, &a), &b) in y.indexed_iter().zip(y_ref.iter()) {
+            let err = if a == b || (a.is_nan() && b.is_nan()) {
+                0.
+            } else {
+                let err = (a - b).abs();
+                if err.is_nan() {
+                    f32::INFINITY
+                } else {
+                    err
+                }
+            };
+            if err > self.atol + self.rtol * b.abs() {
+                num_mismatched += 1;
+            }
+            max_abs_err = max_abs_err.max(err);
+            max_rel_err = max_rel_err.max(err / b.abs().max(f32::EPSILON));
+            sum_abs_err += err as f64;
+            if err > 0. && self.topk > 0 {
+                errs.push((err, index.slice().to_vec(), a, b));
+                if errs.len() >= self.topk * 64 {
+                    Self::keep_worst(&mut errs, self.topk);
+                }
+            }
+        }
+        Self::keep_worst(&mut errs, self.topk);
+
+        Ok(ParityReport {
+            name: name.to_string(),
+            max_abs_err,
+            mean_abs_err: (sum_abs_err / y.len().max(1) as f64) as f32,
+            max_rel_err,
+            num_mismatched,
+            num_elements: y.len(),
+            worst: errs.into_iter().map(|(_, i, a, b)| (i, a, b)).collect(),
+        })
+    }
+
+    /// Runs `engine` on the inputs of the case directory and compares every output
+    /// that has a reference. Outputs without a reference are skipped.
+    pub fn run<P: AsRef<Path>>(&self, engine: &mut Engine, dir: P) -> Result<Vec<ParityReport>> {
+        let dir = dir.as_ref();
+        let (inames, onames) = match (engine.inames(), engine.onames()) {
+            (Some(inames), Some(onames)) => (inames.to_vec(), onames.to_vec()),
+            _ => anyhow::bail!("Failed to check parity. No model info found."),
+        };
+
+        let mut xs = XAnys::default();
+        for name in inames.iter() {
+            xs.push_kv(name, XAny::read_npy(Self::npy_file(dir, "inputs", name))?)?;
+        }
+        let ys = engine.run_named(xs)?;
+
+        let mut reports = Vec::new();
+        for name in onames.iter() {
+            let p = Self::npy_file(dir, "outputs", name);
+            if !p.exists() {
+                warn!("No reference found for output `{}`: {}", name, p.display());
+                continue;
+            }
+            let y_ref = XAny::read_npy(&p)?.into_x();
+            let y = ys[name.as_str()].to_x();
+            reports.push(self.compare(name, &y, &y_ref)?);
+        }
+
+        Ok(reports)
+    }
+
+    /// Like [`Parity::run`], but fails with all reports if any output is out of tolerance.
+    pub fn assert<P: AsRef<Path>>(&self, engine: &mut Engine, dir: P) -> Result<Vec<ParityReport>> {
+        let reports = self.run(engine, dir.as_ref())?;
+        if reports.is_empty() {
+            anyhow::bail!(
+                "No references found for any output in: {}",
+                dir.as_ref().display()
+            );
+        }
+        if !reports.iter().all(|x| x.passed()) {
+            let reports: Vec<String> = reports.iter().map(|x| x.to_string()).collect();
+            anyhow::bail!(
+                "Parity check failed for {}:\n{}",
+                engine.spec(),
+                reports.join("\n")
+            );
+        }
+
+        Ok(reports)
+    }
+
+    /// Builds an engine from a committed config and checks it against the case directory.
+    ///
+    /// Meant for golden tests of `Config` presets, e.g.
+    /// `Parity::default().assert_config(&Config::rtmo_s().commit()?.model, "golden/rtmo-s")`.
+    pub fn assert_config<P: AsRef<Path>>(
+        &self,
+        config: &ORTConfig,
+        dir: P,
+    ) -> Result<Vec<ParityReport>> {
+        let mut engine = Engine::try_from_config(&config.clone().with_num_dry_run(0))?;
+        self.assert(&mut engine, dir)
+    }
+
+    fn npy_file(dir: &Path, sub: &str, name: &str) -> PathBuf {
+        dir.join(sub)
+            .join(format!("{}.npy", name.replace(['/', ':'], "_")))
+    }
+
+    fn keep_worst(errs: &mut Vec<(f32, Vec<usize>, f32, f32)>, k: usize) {
+        errs.sort_by(|a, b| b.0.total_cmp(&a.0));
+        errs.truncate(k);
+    }
+}
+
+#[cfg(test)]
+mod tests_parity {
+    use super::Parity;
+    use ndarray::{Array, IxDyn};
+
+    #[test]
+    fn test_compare_pass() {
+        let y = Array::from_shape_vec(IxDyn(&[2, 2]), vec![1., 2., 3., 4.]).unwrap();
+        let y_ref = Array::from_shape_vec(IxDyn(&[2, 2]), vec![1., 2., 3., 4.00001]).unwrap();
+        let report = Parity::default().compare("y", &y, &y_ref).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.worst.len(), 1);
+        assert_eq!(report.worst[0].0, vec![1, 1]);
+    }
+
+    #[test]
+    fn test_compare_fail() {
+        let y = Array::from_shape_vec(IxDyn(&[3]), vec![1., 2.5, f32::NAN]).unwrap();
+        let y_ref = Array::from_shape_vec(IxDyn(&[3]), vec![1., 2., 3.]).unwrap();
+        let report = Parity::default()
+            .with_topk(1)
+            .compare("y", &y, &y_ref)
+            .unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.num_mismatched, 2);
+        assert_eq!(report.max_abs_err, f32::INFINITY);
+        assert_eq!(report.worst.len(), 1);
+        assert_eq!(report.worst[0].0, vec![2]);
+    }
+
+    #[test]
+    fn test_compare_shape_mismatch() {
+        let y = Array::zeros(IxDyn(&[2, 3]));
+        let y_ref = Array::zeros(IxDyn(&[3, 2]));
+        assert!(Parity::default().compare("y", &y, &y_ref).is_err());
+    }
+}
@@ -51,6 +51,23 @@ impl Perf {
         Ok(serde_json::to_string_pretty(&data)?)
     }
 
+    /// Merges an ONNX Runtime profiling file into the report under `module`, usually an engine `spec`.
+    ///
+    /// Session events are recorded as `ort/<name>` and kernel runs as `ort/op/<op_type>`.
+    /// Returns the number of merged events.
+    pub fn merge_ort_profile<P: AsRef<std::path::Path>>(
+        module: &str,
+        p: P,
+    ) -> anyhow::Result<usize> {
+        let events = parse_ort_profile(&std::fs::read_to_string(p.as_ref())?)?;
+        let manager = global_ts_manager();
+        for (label, duration) in events.iter() {
+            manager.push_module(module, label, *duration);
+        }
+
+        Ok(events.len())
+    }
+
     /// Show traditional table format
     pub fn table() {
         global_ts_manager().print_enhanced_summary();
@@ -462,6 +479,28 @@ fn print_tree_breakdown(
     }
 }
 
+/// Parses the Chrome trace events written by ONNX Runtime's profiler.
+fn parse_ort_profile(s: &str) -> anyhow::Result<Vec<(String, Duration)>> {
+    let events: Vec<serde_json::Value> = serde_json::from_str(s)?;
+    let ys = events
+        .iter()
+        .filter_map(|x| {
+            let duration = Duration::from_micros(x.get("dur")?.as_u64()?);
+            let name = x.get("name")?.as_str()?;
+            match x.get("cat")?.as_str()? {
+                "Session" => Some((format!("ort/{}", name), duration)),
+                "Node" if name.ends_with("_kernel_time") => {
+                    let op = x.get("args")?.get("op_name")?.as_str()?;
+                    Some((format!("ort/op/{}", op), duration))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+
+    Ok(ys)
+}
+
 /// Show performance data with optional table
 /// Default shows ASCII chart, set show_table=true to include detailed table
 pub fn perf(show_table: bool) {
@@ -532,4 +571,24 @@ mod tests {
 
         clear();
     }
+
+    #[test]
+    fn test_parse_ort_profile() {
+        let s = r#"[
+            {"cat": "Session", "name": "model_run", "dur": 1500, "args": {}},
+            {"cat": "Node", "name": "/conv1/Conv_fence_before", "dur": 0, "args": {"op_name": "Conv"}},
+            {"cat": "Node", "name": "/conv1/Conv_kernel_time", "dur": 700, "args": {"op_name": "Conv"}},
+            {"cat": "Node", "name": "/relu/Relu_kernel_time", "dur": 20, "args": {"op_name": "Relu"}}
+        ]"#;
+        let ys = parse_ort_profile(s).unwrap();
+        assert_eq!(
+            ys,
+            vec![
+                ("ort/model_run".to_string(), Duration::from_micros(1500)),
+                ("ort/op/Conv".to_string(), Duration::from_micros(700)),
+                ("ort/op/Relu".to_string(), Duration::from_micros(20)),
+            ]
+        );
+        assert!(parse_ort_profile("{}").is_err());
+    }
 }
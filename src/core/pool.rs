@@ -0,0 +1,157 @@
+use anyhow::Result;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex};
+
+/// A fixed-size pool of instances handed out to one caller at a time.
+///
+/// `Pool<T>` is `Sync` as long as `T` is `Send`, so one pool can serve `&self` inference
+/// from rayon or std threads. Callers check an instance out with [`Pool::get`], and it goes
+/// back into the pool when the returned guard is dropped.
+#[derive(Debug)]
+pub struct Pool<T> {
+    items: Mutex<Vec<T>>,
+    available: Condvar,
+    size: usize,
+}
+
+impl<T> Pool<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            size: items.len(),
+            items: Mutex::new(items),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Builds a pool of `size` instances, calling `f` with the index of each instance.
+    pub fn try_new(size: usize, mut f: impl FnMut(usize) -> Result<T>) -> Result<Self> {
+        if size == 0 {
+            anyhow::bail!("Failed to build pool. The pool size must be greater than 0.");
+        }
+        let mut items = Vec::with_capacity(size);
+        for i in 0..size {
+            items.push(f(i)?);
+        }
+
+        Ok(Self::new(items))
+    }
+
+    /// Total number of instances owned by the pool.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of instances not checked out right now.
+    pub fn num_idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Checks out an instance, blocking until one is available.
+    pub fn get(&self) -> Pooled<'_, T> {
+        let mut items = self.lock();
+        loop {
+            if let Some(item) = items.pop() {
+                return Pooled {
+                    pool: self,
+                    item: Some(item),
+                };
+            }
+            items = self
+                .available
+                .wait(items)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+
+    /// Checks out an instance if one is idle, without blocking.
+    pub fn try_get(&self) -> Option<Pooled<'_, T>> {
+        self.lock().pop().map(|item| Pooled {
+            pool: self,
+            item: Some(item),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<T>> {
+        // A panic while an instance was checked out does not leave the queue inconsistent.
+        self.items.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn put(&self, item: T) {
+        self.lock().push(item);
+        self.available.notify_one();
+    }
+}
+
+/// An instance checked out of a [`Pool`], returned to it on drop.
+#[derive(Debug)]
+pub struct Pooled<'a, T> {
+    pool: &'a Pool<T>,
+    item: Option<T>,
+}
+
+impl<T> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.item
+            .as_ref()
+            .expect("Pooled instance has already been returned")
+    }
+}
+
+impl<T> DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.item
+            .as_mut()
+            .expect("Pooled instance has already been returned")
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.pool.put(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_pool {
+    use super::Pool;
+
+    #[test]
+    fn test_empty() {
+        assert!(Pool::<usize>::try_new(0, Ok).is_err());
+    }
+
+    #[test]
+    fn test_get_and_return() {
+        let pool = Pool::try_new(2, Ok).unwrap();
+        assert_eq!(pool.size(), 2);
+        {
+            let a = pool.get();
+            let b = pool.get();
+            assert_ne!(*a, *b);
+            assert_eq!(pool.num_idle(), 0);
+            assert!(pool.try_get().is_none());
+        }
+        assert_eq!(pool.num_idle(), 2);
+    }
+
+    #[test]
+    fn test_threads() {
+        let pool = Pool::try_new(3, |_| Ok(0usize)).unwrap();
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        *pool.get() += 1;
+                    }
+                });
+            }
+        });
+        let items: Vec<_> = (0..3).map(|_| pool.try_get().unwrap()).collect();
+        let total: usize = items.iter().map(|x| **x).sum();
+        assert_eq!(total, 800);
+    }
+}
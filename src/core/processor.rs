@@ -5,7 +5,9 @@ use rayon::prelude::*;
 use std::sync::Mutex;
 use tokenizers::{Encoding, Tokenizer};
 
-use crate::{Hub, Image, ImageTransformInfo, LogitsSampler, ProcessorConfig, ResizeMode, X};
+use crate::{
+    Hub, Image, ImageTransformInfo, LogitsSampler, ProcessorConfig, ResizeMode, ShapeBucket, X,
+};
 
 /// Image and text processing pipeline with tokenization and transformation capabilities.
 #[derive(Builder, Debug, Clone)]
@@ -28,6 +30,7 @@ pub struct Processor {
     pub pad_size: usize,
     pub up_scale: f32,
     pub do_resize: bool,
+    pub shape_bucket: Option<ShapeBucket>,
 }
 
 impl Default for Processor {
@@ -51,6 +54,7 @@ impl Default for Processor {
             pad_size: 8,
             up_scale: 2.,
             do_resize: true,
+            shape_bucket: None,
         }
     }
 }
@@ -95,6 +99,7 @@ impl Processor {
             pad_image: config.pad_image,
             pad_size: config.pad_size,
             up_scale: config.up_scale,
+            shape_bucket: config.shape_bucket.clone(),
             tokenizer,
             vocab,
             logits_sampler: Some(logits_sampler),
@@ -141,6 +146,10 @@ impl Processor {
     }
 
     pub fn par_resize(&self, xs: &[Image]) -> Result<(X, Vec<ImageTransformInfo>)> {
+        if let Some(shape_bucket) = &self.shape_bucket {
+            return self.par_resize_bucketed(xs, shape_bucket);
+        }
+
         match xs.len() {
             0 => anyhow::bail!("Found no input images."),
             1 => {
@@ -199,6 +208,114 @@ impl Processor {
         }
     }
 
+    /// Resizes images within `image_width` x `image_height` according to `resize_mode`,
+    /// then pads them (bottom and right) up to the bucket of the largest one in the batch.
+    ///
+    /// The padding is recorded in `width_pad` and `height_pad` of [`ImageTransformInfo`],
+    /// and `width_dst` and `height_dst` are the bucket size.
+    fn par_resize_bucketed(
+        &self,
+        xs: &[Image],
+        shape_bucket: &ShapeBucket,
+    ) -> Result<(X, Vec<ImageTransformInfo>)> {
+        if xs.is_empty() {
+            anyhow::bail!("Found no input images.");
+        }
+        if self.image_width + self.image_height == 0 {
+            anyhow::bail!(
+                "Invalid target height: {} or width: {}.",
+                self.image_height,
+                self.image_width
+            );
+        }
+
+        let sizes: Vec<(u32, u32)> = xs
+            .iter()
+            .map(|x| self.fit_size(x.width(), x.height()))
+            .collect();
+        let (w, h) = sizes
+            .iter()
+            .fold((0, 0), |acc, &(w, h)| (acc.0.max(w), acc.1.max(h)));
+        let (tw, th) = shape_bucket.fit(w, h);
+
+        let ys = Mutex::new(
+            Array::from_elem(
+                (xs.len(), th as usize, tw as usize, 3),
+                self.padding_value as f32,
+            )
+            .into_dyn(),
+        );
+
+        let results: Result<Vec<ImageTransformInfo>> = xs
+            .par_iter()
+            .zip(sizes.par_iter())
+            .enumerate()
+            .map(|(idx, (x, &(w, h)))| {
+                // shrink when no bucket fits
+                let r = (tw as f32 / w as f32).min(th as f32 / h as f32).min(1.);
+                let (w, h) = (
+                    ((w as f32 * r).round() as u32).max(1),
+                    ((h as f32 * r).round() as u32).max(1),
+                );
+                let (image, trans_info) = x.resize_with_info(
+                    w,
+                    h,
+                    self.resize_filter,
+                    &ResizeMode::FitExact,
+                    self.padding_value,
+                )?;
+                let trans_info = trans_info
+                    .with_width_dst(tw)
+                    .with_height_dst(th)
+                    .with_width_pad((tw - w) as f32)
+                    .with_height_pad((th - h) as f32);
+
+                let y = image.to_ndarray()?;
+                {
+                    let mut ys_guard = ys
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("Mutex lock error: {e}"))?;
+                    ys_guard
+                        .slice_mut(s![idx, ..h as usize, ..w as usize, ..])
+                        .assign(&y);
+                }
+
+                Ok(trans_info)
+            })
+            .collect();
+
+        let ys_inner = ys
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Mutex into_inner error: {e}"))?;
+
+        Ok((ys_inner.into(), results?))
+    }
+
+    /// Size of an image resized within `image_width` x `image_height`, before padding.
+    fn fit_size(&self, w0: u32, h0: u32) -> (u32, u32) {
+        let (tw, th) = (self.image_width, self.image_height);
+        let (w, h) = match self.resize_mode {
+            ResizeMode::FitExact => (tw, th),
+            ResizeMode::FitAdaptive | ResizeMode::Letterbox => {
+                let r = (tw as f32 / w0 as f32).min(th as f32 / h0 as f32);
+                (
+                    (w0 as f32 * r).round() as u32,
+                    (h0 as f32 * r).round() as u32,
+                )
+            }
+            ResizeMode::FitHeight => {
+                let r = th as f32 / h0 as f32;
+                (((w0 as f32 * r).round() as u32).min(tw), th)
+            }
+            ResizeMode::FitWidth => {
+                let r = tw as f32 / w0 as f32;
+                (tw, ((h0 as f32 * r).round() as u32).min(th))
+            }
+        };
+
+        (w.max(1), h.max(1))
+    }
+
     pub fn encode_text(&self, x: &str, skip_special_tokens: bool) -> Result<Encoding> {
         let tokenizer = self.tokenizer.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
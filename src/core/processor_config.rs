@@ -1,11 +1,13 @@
 use aksr::Builder;
 use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize};
 use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
 
-use crate::{Hub, ResizeMode};
+use crate::{Hub, ResizeMode, ShapeBucket};
 
 /// Configuration for image and text processing pipelines.
-#[derive(Builder, Debug, Clone)]
+#[derive(Builder, Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ProcessorConfig {
     // Vision
     /// Target image width for resizing.
@@ -17,7 +19,9 @@ pub struct ProcessorConfig {
     /// Image resizing mode.
     pub resize_mode: ResizeMode,
     /// Image resize filter algorithm.
-    pub resize_filter: Option<&'static str>,
+    // `std::primitive::str` keeps serde from requiring `'static` input for the whole struct
+    #[serde(deserialize_with = "deserialize_resize_filter")]
+    pub resize_filter: Option<&'static std::primitive::str>,
     /// Padding value for image borders.
     pub padding_value: u8,
     /// Whether to normalize image values.
@@ -36,6 +40,8 @@ pub struct ProcessorConfig {
     pub pad_size: usize,
     /// Up-scaling factor for super resolution.
     pub up_scale: f32,
+    /// Shape bucketing policy for dynamic-shape models.
+    pub shape_bucket: Option<ShapeBucket>,
 
     // Text
     /// Maximum sequence length for tokenization.
@@ -77,6 +83,7 @@ impl Default for ProcessorConfig {
             pad_image: false,
             pad_size: 8,
             up_scale: 2.,
+            shape_bucket: None,
             model_max_length: None,
             tokenizer_file: None,
             config_file: None,
@@ -91,6 +98,33 @@ impl Default for ProcessorConfig {
     }
 }
 
+/// Resize filters supported by [`crate::build_resizer_filter`].
+pub(crate) const RESIZE_FILTERS: [&str; 7] = [
+    "Box",
+    "Bilinear",
+    "Hamming",
+    "CatmullRom",
+    "Mitchell",
+    "Gaussian",
+    "Lanczos3",
+];
+
+/// Parses a resize filter case-insensitively, returning its canonical name.
+fn deserialize_resize_filter<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<&'static str>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(x) => match RESIZE_FILTERS.iter().find(|f| f.eq_ignore_ascii_case(&x)) {
+            Some(f) => Ok(Some(*f)),
+            None => Err(serde::de::Error::custom(format!(
+                "unknown resize filter `{}`, expected one of {:?}",
+                x, RESIZE_FILTERS
+            ))),
+        },
+    }
+}
+
 impl ProcessorConfig {
     pub fn try_build_tokenizer(&self) -> Result<Option<Tokenizer>> {
         let mut hub = Hub::default();
@@ -242,6 +276,10 @@ macro_rules! impl_processor_config_methods {
                 self.$field = self.$field.with_up_scale(up_scale);
                 self
             }
+            pub fn with_shape_bucket(mut self, shape_bucket: $crate::ShapeBucket) -> Self {
+                self.$field = self.$field.with_shape_bucket(shape_bucket);
+                self
+            }
             pub fn with_model_max_length(mut self, model_max_length: u64) -> Self {
                 self.$field = self.$field.with_model_max_length(model_max_length);
                 self
@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 /// Model scale variants for different model sizes.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum Scale {
     N,
     T,
@@ -44,6 +46,20 @@ impl std::fmt::Display for Scale {
     }
 }
 
+impl TryFrom<String> for Scale {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Scale> for String {
+    fn from(x: Scale) -> Self {
+        x.to_string()
+    }
+}
+
 impl TryFrom<char> for Scale {
     type Error = anyhow::Error;
 
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Ord, Eq, PartialOrd, PartialEq)]
+/// Serialized by variant name, e.g. `"ObjectDetection"` or `{ Vqa = "What's in this image?" }`.
+#[derive(Debug, Clone, Ord, Eq, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub enum Task {
     /// Image classification task.
     /// Input: image
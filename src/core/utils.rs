@@ -177,3 +177,56 @@ pub fn timestamp(delimiter: Option<&str>) -> String {
     let format = format!("%Y{0}%m{0}%d{0}%H{0}%M{0}%S{0}%f", delimiter);
     chrono::Local::now().format(&format).to_string()
 }
+
+/// Calls `f` on consecutive chunks of at most `max_batch` items and concatenates
+/// the results in order.
+///
+/// With `None`, or when all items fit, `f` is called once on all of them.
+///
+/// # Example
+/// ```ignore
+/// let ys = run_in_batches(&[1, 2, 3, 4, 5], Some(2), |xs| Ok(vec![xs.len()]))?;
+/// assert_eq!(ys, [2, 2, 1]);
+/// ```
+pub fn run_in_batches<T, Y>(
+    xs: &[T],
+    max_batch: Option<usize>,
+    mut f: impl FnMut(&[T]) -> anyhow::Result<Vec<Y>>,
+) -> anyhow::Result<Vec<Y>> {
+    match max_batch {
+        Some(n) if n > 0 && xs.len() > n => {
+            let mut ys = Vec::with_capacity(xs.len());
+            for xs in xs.chunks(n) {
+                ys.extend(f(xs)?);
+            }
+            Ok(ys)
+        }
+        _ => f(xs),
+    }
+}
+
+/// [`run_in_batches`] for image models, keeping the frame metadata of each image.
+pub fn forward_in_batches(
+    xs: &[crate::Image],
+    max_batch: Option<usize>,
+    f: impl FnMut(&[crate::Image]) -> anyhow::Result<Vec<crate::Y>>,
+) -> anyhow::Result<Vec<crate::Y>> {
+    let ys = run_in_batches(xs, max_batch, f)?;
+    Ok(keep_frames(xs, ys))
+}
+
+/// Copies the [`crate::FrameMeta`] of each image to its result.
+///
+/// Results are left as they are unless there is exactly one per image.
+pub fn keep_frames(xs: &[crate::Image], ys: Vec<crate::Y>) -> Vec<crate::Y> {
+    if xs.len() != ys.len() {
+        return ys;
+    }
+    ys.into_iter()
+        .zip(xs)
+        .map(|(y, x)| match x.frame() {
+            Some(frame) => y.with_frame(frame.clone()),
+            None => y,
+        })
+        .collect()
+}
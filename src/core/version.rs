@@ -1,8 +1,25 @@
 use aksr::Builder;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 /// Version representation with major, minor, and optional patch numbers.
-#[derive(Debug, Builder, PartialEq, Eq, Copy, Clone, Hash, Default, PartialOrd, Ord)]
+///
+/// Serialized as a string like `"v8"`, `"v1.5"` or `"v1.2.3"`.
+#[derive(
+    Debug,
+    Builder,
+    PartialEq,
+    Eq,
+    Copy,
+    Clone,
+    Hash,
+    Default,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
 pub struct Version(pub u8, pub u8, pub Option<u8>);
 
 impl Version {
@@ -44,6 +61,43 @@ impl From<(u8, u8, u8)> for Version {
     }
 }
 
+impl std::str::FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let xs = s
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(s)
+            .split('.')
+            .map(|x| x.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| {
+                anyhow::anyhow!("Invalid version: {:?}. Expected e.g. `v8` or `v1.5`", s)
+            })?;
+        match xs[..] {
+            [major] => Ok(Self(major, 0, None)),
+            [major, minor] => Ok(Self(major, minor, None)),
+            [major, minor, patch] => Ok(Self(major, minor, Some(patch))),
+            _ => anyhow::bail!("Invalid version: {:?}. Expected e.g. `v8` or `v1.5`", s),
+        }
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Version> for String {
+    fn from(x: Version) -> Self {
+        x.to_string()
+    }
+}
+
 impl TryFrom<f32> for Version {
     type Error = anyhow::Error;
 
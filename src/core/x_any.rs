@@ -0,0 +1,335 @@
+use anyhow::Result;
+use half::{bf16, f16};
+use ndarray::{Array, IxDyn};
+
+use crate::{DType, X};
+
+/// Typed tensor: holds an [`Array<T, IxDyn>`] in its native element type.
+///
+/// Unlike [`X`], which is always `f32`, `XAny` keeps integer, half-precision and boolean
+/// tensors as-is, so they can be handed to the `Engine` without an extra cast or copy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XAny {
+    Fp16(Array<f16, IxDyn>),
+    Bf16(Array<bf16, IxDyn>),
+    Fp32(Array<f32, IxDyn>),
+    Fp64(Array<f64, IxDyn>),
+    Int8(Array<i8, IxDyn>),
+    Int16(Array<i16, IxDyn>),
+    Int32(Array<i32, IxDyn>),
+    Int64(Array<i64, IxDyn>),
+    Uint8(Array<u8, IxDyn>),
+    Uint16(Array<u16, IxDyn>),
+    Uint32(Array<u32, IxDyn>),
+    Uint64(Array<u64, IxDyn>),
+    Bool(Array<bool, IxDyn>),
+}
+
+impl Default for XAny {
+    fn default() -> Self {
+        Self::Fp32(Array::zeros(0).into_dyn())
+    }
+}
+
+macro_rules! impl_x_any_conversions {
+    ($($variant:ident => $ty:ty),* $(,)?) => {
+        $(
+            impl From<Array<$ty, IxDyn>> for XAny {
+                fn from(x: Array<$ty, IxDyn>) -> Self {
+                    Self::$variant(x)
+                }
+            }
+
+            impl TryFrom<XAny> for Array<$ty, IxDyn> {
+                type Error = anyhow::Error;
+
+                fn try_from(x: XAny) -> Result<Self, Self::Error> {
+                    match x {
+                        XAny::$variant(x) => Ok(x),
+                        x => anyhow::bail!(
+                            "Failed to convert XAny of {} into Array<{}>",
+                            x.dtype(),
+                            stringify!($ty)
+                        ),
+                    }
+                }
+            }
+        )*
+
+        impl XAny {
+            /// Element type of this tensor.
+            pub fn dtype(&self) -> DType {
+                match self {
+                    $(Self::$variant(_) => DType::$variant,)*
+                }
+            }
+
+            pub fn dims(&self) -> &[usize] {
+                match self {
+                    $(Self::$variant(x) => x.shape(),)*
+                }
+            }
+
+            pub fn ndim(&self) -> usize {
+                self.dims().len()
+            }
+
+            pub fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(x) => x.len(),)*
+                }
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+    };
+}
+
+impl_x_any_conversions!(
+    Fp16 => f16,
+    Bf16 => bf16,
+    Fp32 => f32,
+    Fp64 => f64,
+    Int8 => i8,
+    Int16 => i16,
+    Int32 => i32,
+    Int64 => i64,
+    Uint8 => u8,
+    Uint16 => u16,
+    Uint32 => u32,
+    Uint64 => u64,
+    Bool => bool,
+);
+
+impl From<X> for XAny {
+    fn from(x: X) -> Self {
+        Self::Fp32(x.0)
+    }
+}
+
+impl From<Vec<i64>> for XAny {
+    fn from(x: Vec<i64>) -> Self {
+        Self::Int64(Array::from_vec(x).into_dyn())
+    }
+}
+
+impl XAny {
+    /// Casts into an `f32` tensor. No copy is made if it is already `f32`.
+    pub fn into_x(self) -> X {
+        match self {
+            Self::Fp32(x) => X::from(x),
+            x => x.to_x(),
+        }
+    }
+
+    /// Casts into an `f32` tensor, always copying.
+    pub fn to_x(&self) -> X {
+        let x = match self {
+            Self::Fp16(x) => x.mapv(f16::to_f32),
+            Self::Bf16(x) => x.mapv(bf16::to_f32),
+            Self::Fp32(x) => x.clone(),
+            Self::Fp64(x) => x.mapv(|x| x as f32),
+            Self::Int8(x) => x.mapv(|x| x as f32),
+            Self::Int16(x) => x.mapv(|x| x as f32),
+            Self::Int32(x) => x.mapv(|x| x as f32),
+            Self::Int64(x) => x.mapv(|x| x as f32),
+            Self::Uint8(x) => x.mapv(|x| x as f32),
+            Self::Uint16(x) => x.mapv(|x| x as f32),
+            Self::Uint32(x) => x.mapv(|x| x as f32),
+            Self::Uint64(x) => x.mapv(|x| x as f32),
+            Self::Bool(x) => x.mapv(|x| x as u8 as f32),
+        };
+        X::from(x)
+    }
+
+    /// Reads a `.npy` file, keeping its element type.
+    pub fn read_npy<P: AsRef<std::path::Path>>(p: P) -> Result<Self> {
+        use ndarray_npy::ReadNpyExt;
+
+        fn _read<T>(x: &[u8]) -> Option<XAny>
+        where
+            T: ndarray_npy::ReadableElement,
+            Array<T, IxDyn>: Into<XAny>,
+        {
+            Array::<T, IxDyn>::read_npy(x).ok().map(Into::into)
+        }
+
+        let x = std::fs::read(p.as_ref()).map_err(|err| {
+            anyhow::anyhow!("Failed to read npy file: {}. {}", p.as_ref().display(), err)
+        })?;
+        _read::<f32>(&x)
+            .or_else(|| _read::<f64>(&x))
+            .or_else(|| _read::<i64>(&x))
+            .or_else(|| _read::<i32>(&x))
+            .or_else(|| _read::<i16>(&x))
+            .or_else(|| _read::<i8>(&x))
+            .or_else(|| _read::<u64>(&x))
+            .or_else(|| _read::<u32>(&x))
+            .or_else(|| _read::<u16>(&x))
+            .or_else(|| _read::<u8>(&x))
+            .or_else(|| _read::<bool>(&x))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Failed to read npy file: {}. Unsupported dtype or invalid file.",
+                    p.as_ref().display()
+                )
+            })
+    }
+
+    pub fn from_shape_vec<T>(shape: &[usize], xs: Vec<T>) -> Result<Self>
+    where
+        Array<T, IxDyn>: Into<Self>,
+    {
+        Ok(Array::from_shape_vec(shape, xs)?.into())
+    }
+
+    /// Casts into `dtype` element by element, like `as`.
+    ///
+    /// Unlike going through [`X`], integers are cast to integers and floats directly, so
+    /// e.g. token ids above 2^24 keep their values when fed as `i64`.
+    pub fn cast(self, dtype: DType) -> Result<Self> {
+        if self.dtype() == dtype {
+            return Ok(self);
+        }
+
+        fn _cast<S: Cast>(x: &Array<S, IxDyn>, dtype: DType) -> Result<XAny> {
+            fn _to<S: Cast, T: Cast>(x: &Array<S, IxDyn>) -> Array<T, IxDyn> {
+                x.mapv(|x| T::from_num(x.to_num()))
+            }
+
+            let x = match dtype {
+                DType::Fp16 => XAny::Fp16(_to(x)),
+                DType::Bf16 => XAny::Bf16(_to(x)),
+                DType::Fp32 => XAny::Fp32(_to(x)),
+                DType::Fp64 => XAny::Fp64(_to(x)),
+                DType::Int8 => XAny::Int8(_to(x)),
+                DType::Int16 => XAny::Int16(_to(x)),
+                DType::Int32 => XAny::Int32(_to(x)),
+                DType::Int64 => XAny::Int64(_to(x)),
+                DType::Uint8 => XAny::Uint8(_to(x)),
+                DType::Uint16 => XAny::Uint16(_to(x)),
+                DType::Uint32 => XAny::Uint32(_to(x)),
+                DType::Uint64 => XAny::Uint64(_to(x)),
+                DType::Bool => XAny::Bool(_to(x)),
+                _ => anyhow::bail!("Unsupported cast to {}", dtype),
+            };
+            Ok(x)
+        }
+
+        match &self {
+            Self::Fp16(x) => _cast(x, dtype),
+            Self::Bf16(x) => _cast(x, dtype),
+            Self::Fp32(x) => _cast(x, dtype),
+            Self::Fp64(x) => _cast(x, dtype),
+            Self::Int8(x) => _cast(x, dtype),
+            Self::Int16(x) => _cast(x, dtype),
+            Self::Int32(x) => _cast(x, dtype),
+            Self::Int64(x) => _cast(x, dtype),
+            Self::Uint8(x) => _cast(x, dtype),
+            Self::Uint16(x) => _cast(x, dtype),
+            Self::Uint32(x) => _cast(x, dtype),
+            Self::Uint64(x) => _cast(x, dtype),
+            Self::Bool(x) => _cast(x, dtype),
+        }
+    }
+}
+
+/// A value of any element type, wide enough to hold all of them exactly.
+#[derive(Debug, Clone, Copy)]
+enum Num {
+    Int(i128),
+    Float(f64),
+}
+
+/// Element types that can be cast through [`Num`].
+trait Cast: Copy {
+    fn to_num(self) -> Num;
+    fn from_num(x: Num) -> Self;
+}
+
+macro_rules! impl_cast_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Cast for $ty {
+                fn to_num(self) -> Num {
+                    Num::Int(self as i128)
+                }
+
+                fn from_num(x: Num) -> Self {
+                    match x {
+                        Num::Int(x) => x as Self,
+                        Num::Float(x) => x as Self,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_cast_float {
+    ($($ty:ty => $to_f64:expr, $from_f64:expr);* $(;)?) => {
+        $(
+            impl Cast for $ty {
+                fn to_num(self) -> Num {
+                    Num::Float($to_f64(self))
+                }
+
+                fn from_num(x: Num) -> Self {
+                    match x {
+                        Num::Int(x) => $from_f64(x as f64),
+                        Num::Float(x) => $from_f64(x),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_cast_int!(i8, i16, i32, i64, u8, u16, u32, u64);
+impl_cast_float!(
+    f16 => f16::to_f64, f16::from_f64;
+    bf16 => bf16::to_f64, bf16::from_f64;
+    f32 => |x: f32| x as f64, |x: f64| x as f32;
+    f64 => |x: f64| x, |x: f64| x;
+);
+
+impl Cast for bool {
+    fn to_num(self) -> Num {
+        Num::Int(self as i128)
+    }
+
+    fn from_num(x: Num) -> Self {
+        match x {
+            Num::Int(x) => x != 0,
+            Num::Float(x) => x != 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_x_any {
+    use super::XAny;
+    use crate::DType;
+
+    #[test]
+    fn test_cast() {
+        let ids = XAny::from(vec![(1i64 << 40) + 1, 3, -7]);
+        let x = ids.clone().cast(DType::Fp64).unwrap();
+        assert_eq!(x.cast(DType::Int64).unwrap(), ids);
+
+        let x = XAny::from(vec![(1i64 << 24) + 1])
+            .cast(DType::Fp32)
+            .unwrap();
+        assert_eq!(x, XAny::from(ndarray::arr1(&[16777216f32]).into_dyn()));
+        let x = XAny::from(ndarray::arr1(&[0.5f32, 2.7, -1.]).into_dyn());
+        let x = x.cast(DType::Uint8).unwrap();
+        assert_eq!(x, XAny::from(ndarray::arr1(&[0u8, 2, 0]).into_dyn()));
+        let x = x.cast(DType::Bool).unwrap();
+        assert_eq!(
+            x,
+            XAny::from(ndarray::arr1(&[false, true, false]).into_dyn())
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ops::{Deref, Index};
+
+use crate::{generate_random_string, XAny, Xs};
+
+/// Collection of named typed tensors, the [`XAny`] counterpart of [`Xs`].
+#[derive(Debug, Default, Clone)]
+pub struct XAnys {
+    map: HashMap<String, XAny>,
+    names: Vec<String>,
+}
+
+impl From<XAny> for XAnys {
+    fn from(x: XAny) -> Self {
+        let mut xs = Self::default();
+        xs.push(x);
+        xs
+    }
+}
+
+impl From<Vec<XAny>> for XAnys {
+    fn from(xs: Vec<XAny>) -> Self {
+        let mut ys = Self::default();
+        for x in xs {
+            ys.push(x);
+        }
+        ys
+    }
+}
+
+impl From<&Xs> for XAnys {
+    fn from(xs: &Xs) -> Self {
+        let mut ys = Self::default();
+        for name in xs.names() {
+            ys.names.push(name.clone());
+            ys.map
+                .insert(name.clone(), XAny::from(xs[name.as_str()].clone()));
+        }
+        ys
+    }
+}
+
+impl XAnys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn push(&mut self, value: XAny) {
+        loop {
+            let key = generate_random_string(5);
+            if !self.map.contains_key(&key) {
+                self.names.push(key.to_string());
+                self.map.insert(key.to_string(), value);
+                break;
+            }
+        }
+    }
+
+    pub fn push_kv(&mut self, key: &str, value: XAny) -> Result<()> {
+        if !self.map.contains_key(key) {
+            self.names.push(key.to_string());
+            self.map.insert(key.to_string(), value);
+            Ok(())
+        } else {
+            anyhow::bail!("XAnys already contains key: {:?}", key)
+        }
+    }
+
+    /// Consumes the collection, yielding tensors in insertion order.
+    pub fn into_values(mut self) -> Vec<XAny> {
+        self.names
+            .iter()
+            .filter_map(|name| self.map.remove(name))
+            .collect()
+    }
+
+    /// Consumes the collection, yielding `(name, tensor)` pairs in insertion order.
+    pub fn into_pairs(mut self) -> Vec<(String, XAny)> {
+        std::mem::take(&mut self.names)
+            .into_iter()
+            .filter_map(|name| self.map.remove(&name).map(|x| (name, x)))
+            .collect()
+    }
+
+    /// Casts every tensor to `f32`, keeping the names and order.
+    pub fn into_xs(self) -> Result<Xs> {
+        let mut ys = Xs::default();
+        for (name, x) in self.into_pairs() {
+            ys.push_kv(&name, x.into_x())?;
+        }
+        Ok(ys)
+    }
+}
+
+impl Deref for XAnys {
+    type Target = HashMap<String, XAny>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl Index<&str> for XAnys {
+    type Output = XAny;
+
+    fn index(&self, index: &str) -> &Self::Output {
+        self.map.get(index).unwrap_or_else(|| {
+            let available_keys: Vec<&str> = self.map.keys().map(|s| s.as_str()).collect();
+            panic!(
+                "Key '{}' was not found in XAnys. Available keys: {:?}",
+                index, available_keys
+            )
+        })
+    }
+}
+
+impl Index<usize> for XAnys {
+    type Output = XAny;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.names
+            .get(index)
+            .and_then(|key| self.map.get(key))
+            .unwrap_or_else(|| {
+                panic!(
+                    "Index {} was not found in XAnys. Available indices: 0..{}",
+                    index,
+                    self.names.len()
+                )
+            })
+    }
+}
+
+impl<'a> IntoIterator for &'a XAnys {
+    type Item = &'a XAny;
+    type IntoIter = std::vec::IntoIter<&'a XAny>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.names
+            .iter()
+            .map(|x| &self.map[x])
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
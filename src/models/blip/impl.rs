@@ -2,7 +2,10 @@ use aksr::Builder;
 use anyhow::Result;
 use ndarray::{s, Axis};
 
-use crate::{elapsed_module, Config, Engine, Image, LogitsSampler, Processor, Xs, X, Y};
+use crate::{
+    elapsed_module, keep_frames, run_or_break, CancelToken, Config, Engine, Image, LogitsSampler,
+    Processor, Xs, X, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct Blip {
@@ -14,6 +17,8 @@ pub struct Blip {
     processor: Processor,
     max_length: usize,
     eos_token_id: u32,
+    #[args(except(setter))]
+    cancel_token: CancelToken,
 }
 
 impl Blip {
@@ -41,9 +46,18 @@ impl Blip {
             height,
             width,
             processor,
+            cancel_token: CancelToken::default(),
         })
     }
 
+    /// Sets the token checked between generation steps, also passed to every engine.
+    pub fn with_cancel_token(mut self, x: CancelToken) -> Self {
+        self.visual = self.visual.with_cancel_token(x.clone());
+        self.textual = self.textual.with_cancel_token(x.clone());
+        self.cancel_token = x;
+        self
+    }
+
     pub fn encode_images(&mut self, xs: &[Image]) -> Result<X> {
         let ys = self.processor.process_images(xs)?;
         self.batch = xs.len(); // update
@@ -63,7 +77,7 @@ impl Blip {
         let image_embeds = elapsed_module!("BLIP", "encode_images", self.encode_images(images)?);
         let ys = elapsed_module!("BLIP", "generate", self.generate(&image_embeds, text)?);
 
-        Ok(ys)
+        Ok(keep_frames(images, ys))
     }
 
     pub fn generate(&mut self, image_embeds: &X, text: Option<&str>) -> Result<Vec<Y>> {
@@ -73,7 +87,13 @@ impl Blip {
         // generate
         let logits_sampler = LogitsSampler::new();
         let mut finished = vec![false; self.batch()];
+        let mut interruption = None;
         for _ in 0..self.max_length {
+            if let Some(x) = self.cancel_token.interrupted() {
+                interruption = Some(x);
+                break;
+            }
+
             let input_ids_nd = token_ids
                 .iter()
                 .map(|tokens| X::from(tokens.clone()).insert_axis(0))
@@ -83,12 +103,15 @@ impl Blip {
             let input_ids_attn_mask = X::ones(input_ids_nd.dims());
 
             // decode
-            let outputs = self.textual.run(Xs::from(vec![
-                input_ids_nd,
-                input_ids_attn_mask,
-                image_embeds.clone(),
-                X::ones(&[self.batch(), image_embeds.dims()[1]]),
-            ]))?;
+            let outputs = run_or_break!(
+                self.textual.run(Xs::from(vec![
+                    input_ids_nd,
+                    input_ids_attn_mask,
+                    image_embeds.clone(),
+                    X::ones(&[self.batch(), image_embeds.dims()[1]]),
+                ])),
+                interruption
+            );
 
             // decode each token for each batch
             for (i, logit) in outputs[0].axis_iter(Axis(0)).enumerate() {
@@ -125,7 +148,13 @@ impl Blip {
 
         let ys = texts
             .into_iter()
-            .map(|x| Y::default().with_texts(&[x.into()]))
+            .map(|x| {
+                let y = Y::default().with_texts(&[x.into()]);
+                match interruption {
+                    Some(x) => y.with_interruption(x),
+                    None => y,
+                }
+            })
             .collect::<Vec<_>>();
 
         Ok(ys)
@@ -1,10 +1,11 @@
 use aksr::Builder;
 use anyhow::Result;
-use ndarray::Axis;
+use ndarray::{s, Axis};
 use rayon::prelude::*;
 
 use crate::{
-    elapsed_module, Config, DynConf, Engine, Hbb, Image, Mask, Obb, Ops, Polygon, Processor, Xs, Y,
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Mask, Obb, Ops,
+    Polygon, Processor, XAny, XAnys, Xs, Y,
 };
 
 /// DB (Differentiable Binarization) model for text detection.
@@ -56,15 +57,22 @@ impl DB {
         })
     }
 
-    fn preprocess(&mut self, xs: &[Image]) -> Result<Xs> {
-        Ok(self.processor.process_images(xs)?.into())
+    fn preprocess(&mut self, xs: &[Image]) -> Result<XAnys> {
+        Ok(XAny::from(self.processor.process_images(xs)?).into())
     }
 
-    fn inference(&mut self, xs: Xs) -> Result<Xs> {
-        self.engine.run(xs)
+    /// Runs on the typed path, so `f32` inputs are moved to the session and the `u8` ones
+    /// are cast once.
+    fn inference(&mut self, xs: XAnys) -> Result<Xs> {
+        self.engine.run_any(xs)?.into_xs()
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("DB", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("DB", "inference", self.inference(ys)?);
         let ys = elapsed_module!("DB", "postprocess", self.postprocess(ys)?);
@@ -79,16 +87,30 @@ impl DB {
             .enumerate()
             .filter_map(|(idx, luma)| {
                 // input image
-                let (image_height, image_width) = (
-                    self.processor.images_transform_info[idx].height_src,
-                    self.processor.images_transform_info[idx].width_src,
-                );
+                let info = &self.processor.images_transform_info[idx];
+                let (image_height, image_width) = (info.height_src, info.width_src);
+
+                // crop the padding of bucketed inputs, which is recorded in the transform info
+                let (h, w) = match luma.shape() {
+                    [.., h, w] => (*h, *w),
+                    _ => return None,
+                };
+                let padded = info.height_pad + info.width_pad > 0.;
+                let (hv, wv) = if padded {
+                    let hv = (h as f32 * (1. - info.height_pad / info.height_dst as f32)).round();
+                    let wv = (w as f32 * (1. - info.width_pad / info.width_dst as f32)).round();
+                    ((hv as usize).clamp(1, h), (wv as usize).clamp(1, w))
+                } else {
+                    (h, w)
+                };
 
                 // reshape
-                let ratio = self.processor.images_transform_info[idx].height_scale;
+                let ratio = info.height_scale;
                 let v = luma
-                    .as_slice()?
-                    .par_iter()
+                    .to_shape((h, w))
+                    .ok()?
+                    .slice(s![..hv, ..wv])
+                    .iter()
                     .map(|x| {
                         if x <= &self.binary_thresh {
                             0u8
@@ -100,11 +122,11 @@ impl DB {
 
                 let luma = Ops::resize_luma8_u8(
                     &v,
-                    self.width as _,
-                    self.height as _,
+                    wv as _,
+                    hv as _,
                     image_width as _,
                     image_height as _,
-                    true,
+                    !padded,
                     "Bilinear",
                 )
                 .ok()?;
@@ -1,7 +1,10 @@
 use aksr::Builder;
 use anyhow::Result;
+use ndarray::s;
 
-use crate::{elapsed_module, Config, Engine, Image, Mask, Ops, Processor, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, Engine, Image, Mask, Ops, Processor, Xs, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct DepthAnything {
@@ -49,11 +52,24 @@ impl DepthAnything {
         let mut ys: Vec<Y> = Vec::new();
         for (idx, luma) in xs[0].axis_iter(ndarray::Axis(0)).enumerate() {
             // image size
-            let (h1, w1) = (
-                self.processor.images_transform_info[idx].height_src,
-                self.processor.images_transform_info[idx].width_src,
-            );
-            let v = luma.into_owned().into_raw_vec_and_offset().0;
+            let info = &self.processor.images_transform_info[idx];
+            let (h1, w1) = (info.height_src, info.width_src);
+
+            // crop the padding of bucketed inputs
+            let (h, w) = match luma.shape() {
+                [.., h, w] => (*h, *w),
+                _ => anyhow::bail!("Unexpected depth shape: {:?}", luma.shape()),
+            };
+            let (h, w, luma) = if info.height_pad + info.width_pad > 0. {
+                let hv = (h as f32 * (1. - info.height_pad / info.height_dst as f32)).round();
+                let wv = (w as f32 * (1. - info.width_pad / info.width_dst as f32)).round();
+                let (hv, wv) = ((hv as usize).clamp(1, h), (wv as usize).clamp(1, w));
+                let luma = luma.into_shape_with_order((h, w))?;
+                (hv, wv, luma.slice(s![..hv, ..wv]).to_owned().into_dyn())
+            } else {
+                (h, w, luma.into_owned())
+            };
+            let v = luma.into_raw_vec_and_offset().0;
             let max_ = v.iter().max_by(|x, y| x.total_cmp(y)).unwrap();
             let min_ = v.iter().min_by(|x, y| x.total_cmp(y)).unwrap();
             let v = v
@@ -61,15 +77,8 @@ impl DepthAnything {
                 .map(|x| (((*x - min_) / (max_ - min_)) * 255.).clamp(0., 255.) as u8)
                 .collect::<Vec<_>>();
 
-            let luma = Ops::resize_luma8_u8(
-                &v,
-                self.width() as _,
-                self.height() as _,
-                w1 as _,
-                h1 as _,
-                false,
-                "Bilinear",
-            )?;
+            let luma =
+                Ops::resize_luma8_u8(&v, w as _, h as _, w1 as _, h1 as _, false, "Bilinear")?;
             ys.push(Y::default().with_masks(&[Mask::new(&luma, w1, h1)?]));
         }
 
@@ -77,6 +86,11 @@ impl DepthAnything {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("DepthAnything", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("DepthAnything", "inference", self.inference(ys)?);
         let ys = elapsed_module!("DepthAnything", "postprocess", self.postprocess(ys)?);
@@ -2,7 +2,9 @@ use aksr::Builder;
 use anyhow::Result;
 use ndarray::Axis;
 
-use crate::{elapsed_module, Config, Engine, Image, Mask, Ops, Processor, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, Engine, Image, Mask, Ops, Processor, Xs, Y,
+};
 
 #[derive(Builder, Debug)]
 pub struct DepthPro {
@@ -81,6 +83,11 @@ impl DepthPro {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("DepthPro", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("DepthPro", "inference", self.inference(ys)?);
         let ys = elapsed_module!("DepthPro", "postprocess", self.postprocess(ys)?);
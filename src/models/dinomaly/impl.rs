@@ -1,5 +1,5 @@
 //! Implementation of the GLASS model: preprocessing, inference, postprocessing.
-use crate::{elapsed_module, Config, Engine, Heatmap, Image, Processor, Xs, Y};
+use crate::{elapsed_module, forward_in_batches, Config, Engine, Heatmap, Image, Processor, Xs, Y};
 use anyhow::Result;
 use image::{GrayImage};
 use log::debug;
@@ -47,6 +47,11 @@ impl Dinomaly {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("DINOMALY", "visual-preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("DINOMALY", "visual-inference", self.inference(ys)?);
         let ys = elapsed_module!("DINOMALY", "visual-postprocess", self.postprocess(ys)?);
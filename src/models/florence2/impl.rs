@@ -1,11 +1,11 @@
 use aksr::Builder;
 use anyhow::Result;
-use ndarray::{s, Axis};
+use ndarray::{s, Array, Axis};
 use rayon::prelude::*;
 
 use crate::{
-    elapsed_module, models::Quantizer, Config, Engine, Hbb, Image, LogitsSampler, Polygon,
-    Processor, Scale, Task, Xs, X, Y,
+    elapsed_module, keep_frames, models::Quantizer, run_or_break, CancelToken, Config, Engine, Hbb,
+    Image, Interruption, LogitsSampler, Polygon, Processor, Scale, Task, XAny, Xs, X, Y,
 };
 
 #[derive(Debug, Builder)]
@@ -25,6 +25,8 @@ pub struct Florence2 {
     width: usize,
     batch: usize,
     processor: Processor,
+    #[args(except(setter))]
+    cancel_token: CancelToken,
 }
 
 impl Florence2 {
@@ -68,9 +70,21 @@ impl Florence2 {
             height,
             width,
             processor,
+            cancel_token: CancelToken::default(),
         })
     }
 
+    /// Sets the token checked between generation steps, also passed to every engine.
+    pub fn with_cancel_token(mut self, x: CancelToken) -> Self {
+        self.vision_encoder = self.vision_encoder.with_cancel_token(x.clone());
+        self.text_embed = self.text_embed.with_cancel_token(x.clone());
+        self.encoder = self.encoder.with_cancel_token(x.clone());
+        self.decoder = self.decoder.with_cancel_token(x.clone());
+        self.decoder_merged = self.decoder_merged.with_cancel_token(x.clone());
+        self.cancel_token = x;
+        self
+    }
+
     fn process_task(task: &Task, image_height: usize, image_width: usize) -> Task {
         // region-related tasks
         match task {
@@ -99,15 +113,24 @@ impl Florence2 {
             .map(|im| {
                 let text = Self::process_task(task, im.height() as _, im.width() as _)
                     .prompt_for_florence2()?;
-                let ids = self.processor.encode_text_ids(&text, true)?;
-                X::from(ids).insert_axis(0)
+                let ids: Vec<i64> = self
+                    .processor
+                    .encode_text(&text, true)?
+                    .get_ids()
+                    .iter()
+                    .map(|&x| x as i64)
+                    .collect();
+                Ok(ids)
             })
-            .collect::<Result<Vec<_>, _>>()?;
-        let x = X::concat(&xs, 0)?;
-        let xs = self.text_embed.run(x.into())?;
-        let x = xs[0].to_owned();
+            .collect::<Result<Vec<_>>>()?;
+        let n = xs.first().map_or(0, |x| x.len());
+        if xs.iter().any(|x| x.len() != n) {
+            anyhow::bail!("Failed to batch Florence2 prompts of different lengths.");
+        }
+        let x = Array::from_shape_vec((xs.len(), n), xs.concat())?;
+        let xs = self.text_embed.run_any(XAny::from(x.into_dyn()))?;
 
-        Ok(x)
+        Ok(xs[0].to_x())
     }
 
     pub fn forward(&mut self, xs_visual: &[Image], x_textual: &Task) -> Result<Vec<Y>> {
@@ -122,15 +145,24 @@ impl Florence2 {
             self.encode_text(x_textual, xs_visual)?
         });
 
-        let generated = elapsed_module!("Florence2", "generate-then-decode", {
+        let (generated, interruption) = elapsed_module!("Florence2", "generate-then-decode", {
             self.generate_then_decode(&visual_embeddings, &textual_embedding)?
         });
 
+        // partial texts are returned as-is, their locations may be incomplete
+        if let Some(x) = interruption {
+            let ys = generated
+                .into_iter()
+                .map(|text| Y::default().with_texts(&[text.into()]).with_interruption(x))
+                .collect();
+            return Ok(keep_frames(xs_visual, ys));
+        }
+
         let ys = elapsed_module!("Florence2", "postprocess", {
             self.postprocess(&generated, xs_visual, x_textual)?
         });
 
-        Ok(ys)
+        Ok(keep_frames(xs_visual, ys))
     }
 
     // decode or postprocess, batch images and one text
@@ -138,7 +170,7 @@ impl Florence2 {
         &mut self,
         visual_embeddings: &X,
         textual_embedding: &X,
-    ) -> Result<Vec<String>> {
+    ) -> Result<(Vec<String>, Option<Interruption>)> {
         // concate image embeddings and prompt embeddings
         let inputs_embeds = visual_embeddings
             .clone()
@@ -168,18 +200,41 @@ impl Florence2 {
             .map(|i| decoder_outputs[i].clone())
             .collect();
 
+        // logits and decoder kvs, the encoder kvs stay the same while generating
+        let outputs: Vec<usize> = std::iter::once(0)
+            .chain(
+                (1..(4 * self.n_kvs) - 2)
+                    .step_by(4)
+                    .flat_map(|i| [i, i + 1]),
+            )
+            .collect();
+        let output_names: Vec<String> = match self.decoder_merged.onames() {
+            Some(names) => outputs.iter().map(|&i| names[i].clone()).collect(),
+            None => anyhow::bail!("Failed to get the outputs of the merged decoder."),
+        };
+        let output_names: Vec<&str> = output_names.iter().map(|x| x.as_str()).collect();
+        decoder_outputs = outputs
+            .iter()
+            .map(|&i| decoder_outputs[i].clone())
+            .collect::<Vec<_>>()
+            .into();
+
         // token ids
         let mut token_ids: Vec<Vec<u32>> = vec![vec![]; self.batch()];
         let mut finished = vec![false; self.batch()];
-        let mut last_tokens: Vec<f32> = vec![0.; self.batch()];
+        let mut last_tokens: Vec<i64> = vec![0; self.batch()];
         let logits_sampler = LogitsSampler::new();
 
         // generate
+        let mut interruption = None;
         for _ in 0..self.max_length {
+            if let Some(x) = self.cancel_token.interrupted() {
+                interruption = Some(x);
+                break;
+            }
+
             let logits = &decoder_outputs[0];
-            let decoder_kvs: Vec<_> = (1..(4 * self.n_kvs) - 2)
-                .step_by(4)
-                .flat_map(|i| [i, i + 1])
+            let decoder_kvs: Vec<_> = (1..decoder_outputs.len())
                 .map(|i| decoder_outputs[i].clone())
                 .collect();
 
@@ -209,7 +264,7 @@ impl Florence2 {
                         token_ids[i].push(token_id);
                     }
                     // update
-                    last_tokens[i] = token_id as f32;
+                    last_tokens[i] = token_id as i64;
                 }
             }
 
@@ -219,8 +274,12 @@ impl Florence2 {
             }
 
             // decode
-            let next_tokens = X::from(last_tokens.clone()).insert_axis(1)?;
-            let inputs_embeds = &self.text_embed.run(Xs::from(next_tokens))?[0].clone();
+            let next_tokens = Array::from_shape_vec((self.batch(), 1), last_tokens.clone())?;
+            let inputs_embeds = &run_or_break!(
+                self.text_embed.run_any(XAny::from(next_tokens.into_dyn())),
+                interruption
+            )[0]
+            .to_x();
             let use_cache = X::ones(&[1]);
             let mut xs = vec![
                 attention_mask.clone(),
@@ -234,7 +293,11 @@ impl Florence2 {
                 xs.push(encoder_kvs[i * 2 + 1].clone());
             }
             xs.push(use_cache);
-            decoder_outputs = self.decoder_merged.run(xs.into())?;
+            decoder_outputs = run_or_break!(
+                self.decoder_merged
+                    .run_with_outputs(xs.into(), &output_names),
+                interruption
+            );
         }
 
         // batch decode
@@ -243,7 +306,7 @@ impl Florence2 {
             .processor
             .decode_tokens_batch(&token_ids, false)?;
 
-        Ok(texts)
+        Ok((texts, interruption))
     }
 
     fn postprocess(
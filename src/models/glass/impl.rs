@@ -1,5 +1,5 @@
 //! Implementation of the GLASS model: preprocessing, inference, postprocessing.
-use crate::{elapsed_module, Config, Engine, Image, Processor, Xs, Y, Heatmap};
+use crate::{elapsed_module, forward_in_batches, Config, Engine, Image, Processor, Xs, Y, Heatmap};
 use anyhow::Result;
 use image::{GrayImage, Luma};
 use log::debug;
@@ -48,6 +48,11 @@ impl GLASS {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("GLASS", "visual-preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("GLASS", "visual-inference", self.inference(ys)?);
         let ys = elapsed_module!("GLASS", "visual-postprocess", self.postprocess(ys)?);
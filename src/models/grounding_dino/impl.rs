@@ -4,7 +4,9 @@ use ndarray::{s, Array2, Axis};
 use rayon::prelude::*;
 use std::fmt::Write;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y,
+};
 
 #[derive(Builder, Debug)]
 /// Grounding DINO model for open-vocabulary object detection.
@@ -76,6 +78,7 @@ impl GroundingDINO {
     fn preprocess(&mut self, xs: &[Image]) -> Result<Xs> {
         // encode images
         let image_embeddings = self.processor.process_images(xs)?;
+        self.batch = xs.len(); // update
 
         // encode texts
         let input_ids = X::from(self.token_ids.clone())
@@ -104,6 +107,11 @@ impl GroundingDINO {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("GroundingDINO", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("GroundingDINO", "inference", self.inference(ys)?);
         let ys = elapsed_module!("GroundingDINO", "postprocess", self.postprocess(ys)?);
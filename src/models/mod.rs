@@ -27,6 +27,7 @@ mod moondream2;
 mod owl;
 mod picodet;
 mod pipeline;
+mod registry;
 mod rfdetr;
 mod rmbg;
 mod rtdetr;
@@ -60,6 +61,7 @@ pub use moondream2::*;
 pub use owl::*;
 pub use picodet::*;
 pub use pipeline::*;
+pub use registry::*;
 pub use rfdetr::*;
 pub use rmbg::*;
 pub use rtdetr::*;
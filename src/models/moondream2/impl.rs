@@ -5,10 +5,13 @@ use ndarray::{s, Array, Array2, Array3, Axis, IxDyn};
 use ndarray_npy::ReadNpyExt;
 
 use crate::{
-    Config, DType, Engine, Hbb, Hub, Image, Keypoint, LogitsSampler, Processor, Scale, Task, Xs, X,
-    Y,
+    keep_frames, run_or_break, CancelToken, Config, DType, Engine, Hbb, Hub, Image, Interruption,
+    Keypoint, LogitsSampler, Processor, Scale, Task, Xs, X, Y,
 };
 
+/// Generated points, boxes, and the interruption that stopped generation early, if any.
+type PointsBoxes = (Vec<Vec<Keypoint>>, Vec<Hbb>, Option<Interruption>);
+
 #[derive(Builder, Debug)]
 pub struct Moondream2 {
     vision_encoder: Engine,
@@ -29,6 +32,8 @@ pub struct Moondream2 {
     patch_size: usize,
     processor: Processor,
     seq_len: usize,
+    #[args(except(setter))]
+    cancel_token: CancelToken,
 }
 
 impl Moondream2 {
@@ -75,9 +80,24 @@ impl Moondream2 {
             patch_size,
             processor,
             seq_len,
+            cancel_token: CancelToken::default(),
         })
     }
 
+    /// Sets the token checked between generation steps, also passed to every engine.
+    pub fn with_cancel_token(mut self, x: CancelToken) -> Self {
+        self.vision_encoder = self.vision_encoder.with_cancel_token(x.clone());
+        self.vision_projection = self.vision_projection.with_cancel_token(x.clone());
+        self.text_decoder = self.text_decoder.with_cancel_token(x.clone());
+        self.text_encoder = self.text_encoder.with_cancel_token(x.clone());
+        self.coord_decoder = self.coord_decoder.map(|e| e.with_cancel_token(x.clone()));
+        self.coord_encoder = self.coord_encoder.map(|e| e.with_cancel_token(x.clone()));
+        self.size_decoder = self.size_decoder.map(|e| e.with_cancel_token(x.clone()));
+        self.size_encoder = self.size_encoder.map(|e| e.with_cancel_token(x.clone()));
+        self.cancel_token = x;
+        self
+    }
+
     pub fn encode_image(&mut self, x: &Image) -> Result<X> {
         let patches_emb = self.encode(x)?.clone().insert_axis(0)?;
         let image_embedding = self.vision_projection.run(patches_emb.into())?[0].to_owned();
@@ -92,23 +112,26 @@ impl Moondream2 {
             ys.push(y);
         }
 
-        Ok(ys)
+        Ok(keep_frames(xs, ys))
     }
 
     pub fn forward_once(&mut self, images: &Image, task: &Task) -> Result<Y> {
+        if let Some(x) = self.cancel_token.interrupted() {
+            return Ok(Y::default().with_interruption(x));
+        }
+
         let image_embedding = self.encode_image(images)?;
         let kv_cache = self.prepare_kv_cache(&image_embedding)?;
 
-        match task {
+        let (y, interruption) = match task {
             Task::Caption(n) => {
                 let input_ids = match n {
                     0 => vec![198., 198., 16438., 8305., 25.],
                     _ => vec![198., 198., 24334., 1159., 25.],
                 };
-                let text = self.generate_text(&input_ids, kv_cache)?;
-                let y = Y::default().with_texts(&[text.into()]);
+                let (text, interruption) = self.generate_text(&input_ids, kv_cache)?;
 
-                Ok(y)
+                (Y::default().with_texts(&[text.into()]), interruption)
             }
             Task::Vqa(query) => {
                 let input_ids: Vec<_> = [198., 198., 24361., 25.]
@@ -118,10 +141,9 @@ impl Moondream2 {
                     .cloned()
                     .collect();
 
-                let text = self.generate_text(&input_ids, kv_cache)?;
-                let y = Y::default().with_texts(&[text.into()]);
+                let (text, interruption) = self.generate_text(&input_ids, kv_cache)?;
 
-                Ok(y)
+                (Y::default().with_texts(&[text.into()]), interruption)
             }
             Task::OpenSetDetection(object) => {
                 let input_ids: Vec<_> = [198., 198., 47504., 25.]
@@ -134,10 +156,10 @@ impl Moondream2 {
                     .chain(&[628.])
                     .cloned()
                     .collect();
-                let (_, y_bboxes) =
+                let (_, y_bboxes, interruption) =
                     self.generate_points_boxes(&input_ids, kv_cache, object, true)?;
 
-                Ok(Y::default().with_hbbs(&y_bboxes))
+                (Y::default().with_hbbs(&y_bboxes), interruption)
             }
             Task::OpenSetKeypointsDetection(object) => {
                 let input_ids: Vec<_> = [198., 198., 12727., 25.]
@@ -150,16 +172,25 @@ impl Moondream2 {
                     .chain(&[628.])
                     .cloned()
                     .collect();
-                let (y_kpts, _) =
+                let (y_kpts, _, interruption) =
                     self.generate_points_boxes(&input_ids, kv_cache, object, false)?;
 
-                Ok(Y::default().with_keypointss(&y_kpts))
+                (Y::default().with_keypointss(&y_kpts), interruption)
             }
             x => anyhow::bail!("Unsupported Moondream2 task: {}", x),
-        }
+        };
+
+        Ok(match interruption {
+            Some(x) => y.with_interruption(x),
+            None => y,
+        })
     }
 
-    fn generate_text(&mut self, input_ids: &[f32], kv_cache: Array<f32, IxDyn>) -> Result<String> {
+    fn generate_text(
+        &mut self,
+        input_ids: &[f32],
+        kv_cache: Array<f32, IxDyn>,
+    ) -> Result<(String, Option<Interruption>)> {
         let input_ids = X::from(input_ids.to_vec()).insert_axis(0)?;
         let mut input_embeds = self.text_encoder.run(Xs::from(input_ids))?[0].to_owned();
         let logits_sampler = LogitsSampler::new();
@@ -169,7 +200,13 @@ impl Moondream2 {
         let mut kv_cache = kv_cache.clone();
 
         // generate
+        let mut interruption = None;
         for _ in 0..self.max_length {
+            if let Some(x) = self.cancel_token.interrupted() {
+                interruption = Some(x);
+                break;
+            }
+
             // TODO
             let input = Xs::from(vec![
                 input_embeds.clone(),
@@ -179,7 +216,7 @@ impl Moondream2 {
                     .into_dyn()
                     .into(),
             ]);
-            let decoder_outputs = self.text_decoder.run(input)?;
+            let decoder_outputs = run_or_break!(self.text_decoder.run(input), interruption);
 
             // update
             let logits = &decoder_outputs["logits"];
@@ -208,12 +245,14 @@ impl Moondream2 {
 
             // encode
             let next_tokens = X::from(vec![token_id as f32]).insert_axis(1)?;
-            input_embeds = self.text_encoder.run(Xs::from(next_tokens))?[0].to_owned();
+            input_embeds =
+                run_or_break!(self.text_encoder.run(Xs::from(next_tokens)), interruption)[0]
+                    .to_owned();
         }
 
         let text = self.processor.decode_tokens(&token_ids, true)?;
 
-        Ok(text)
+        Ok((text, interruption))
     }
 
     fn generate_points_boxes(
@@ -222,7 +261,7 @@ impl Moondream2 {
         kv_cache: Array<f32, IxDyn>,
         object: &str,
         generate_boxes: bool,
-    ) -> Result<(Vec<Vec<Keypoint>>, Vec<Hbb>)> {
+    ) -> Result<PointsBoxes> {
         let mut y_bboxes: Vec<Hbb> = Vec::new();
         let mut y_kpts: Vec<Vec<Keypoint>> = Vec::new();
         let (image_height, image_width) = (
@@ -239,8 +278,17 @@ impl Moondream2 {
         let mut kv_cache = kv_cache;
 
         // generate
+        let mut interruption = None;
         loop {
-            let logits = self.run_decoder(&mut hidden, &mut kv_cache, &mut pos)?;
+            if let Some(x) = self.cancel_token.interrupted() {
+                interruption = Some(x);
+                break;
+            }
+
+            let logits = run_or_break!(
+                self.run_decoder(&mut hidden, &mut kv_cache, &mut pos),
+                interruption
+            );
 
             // decode
             let token_id = logits_sampler.decode(
@@ -257,25 +305,38 @@ impl Moondream2 {
 
             // cx
             let input: X = hidden.slice(s![0, -1, ..]).into_owned().into_dyn().into();
-            let cx = self.coord_decoder.as_mut().unwrap().run(Xs::from(input))?[0].clone(); // [1024]
+            let cx = run_or_break!(
+                self.coord_decoder.as_mut().unwrap().run(Xs::from(input)),
+                interruption
+            )[0]
+            .clone(); // [1024]
             let ratio = cx.shape()[0] as f32;
             let cx = logits_sampler
                 .decode(cx.as_slice().context("Failed to get slice for `cx`")?)?
                 as f32
                 / ratio;
-            hidden = self
-                .coord_encoder
-                .as_mut()
-                .unwrap()
-                .run(Xs::from(X::from(vec![cx])))?[0]
-                .clone()
-                .insert_axis(0)?
-                .insert_axis(0)?;
+            hidden = run_or_break!(
+                self.coord_encoder
+                    .as_mut()
+                    .unwrap()
+                    .run(Xs::from(X::from(vec![cx]))),
+                interruption
+            )[0]
+            .clone()
+            .insert_axis(0)?
+            .insert_axis(0)?;
 
             // cy
-            let _logits = self.run_decoder(&mut hidden, &mut kv_cache, &mut pos)?;
+            let _logits = run_or_break!(
+                self.run_decoder(&mut hidden, &mut kv_cache, &mut pos),
+                interruption
+            );
             let input: X = hidden.slice(s![0, -1, ..]).into_owned().into_dyn().into();
-            let cy = self.coord_decoder.as_mut().unwrap().run(Xs::from(input))?[0].clone();
+            let cy = run_or_break!(
+                self.coord_decoder.as_mut().unwrap().run(Xs::from(input)),
+                interruption
+            )[0]
+            .clone();
             let ratio = cy.shape()[0] as f32;
 
             let cy = logits_sampler
@@ -283,14 +344,16 @@ impl Moondream2 {
                 as f32
                 / ratio;
 
-            hidden = self
-                .coord_encoder
-                .as_mut()
-                .unwrap()
-                .run(Xs::from(X::from(vec![cy])))?[0]
-                .clone()
-                .insert_axis(0)?
-                .insert_axis(0)?;
+            hidden = run_or_break!(
+                self.coord_encoder
+                    .as_mut()
+                    .unwrap()
+                    .run(Xs::from(X::from(vec![cy]))),
+                interruption
+            )[0]
+            .clone()
+            .insert_axis(0)?
+            .insert_axis(0)?;
 
             if !generate_boxes {
                 y_kpts.push(vec![Keypoint::from((
@@ -307,9 +370,16 @@ impl Moondream2 {
                 }
             } else {
                 // wh
-                let _logits = self.run_decoder(&mut hidden, &mut kv_cache, &mut pos)?;
+                let _logits = run_or_break!(
+                    self.run_decoder(&mut hidden, &mut kv_cache, &mut pos),
+                    interruption
+                );
                 let input: X = hidden.slice(s![0, -1, ..]).into_owned().into_dyn().into();
-                let size = self.size_decoder.as_mut().unwrap().run(Xs::from(input))?[0].clone(); // [2, 1024]
+                let size = run_or_break!(
+                    self.size_decoder.as_mut().unwrap().run(Xs::from(input)),
+                    interruption
+                )[0]
+                .clone(); // [2, 1024]
 
                 let ratio = size.shape()[1] as f32;
                 let w = logits_sampler.decode(
@@ -327,14 +397,16 @@ impl Moondream2 {
                 )? as f32
                     / ratio;
 
-                hidden = self
-                    .size_encoder
-                    .as_mut()
-                    .unwrap()
-                    .run(Xs::from(X::from(vec![w, h])))?[0]
-                    .clone()
-                    .insert_axis(0)?
-                    .insert_axis(0)?; // [1024]
+                hidden = run_or_break!(
+                    self.size_encoder
+                        .as_mut()
+                        .unwrap()
+                        .run(Xs::from(X::from(vec![w, h]))),
+                    interruption
+                )[0]
+                .clone()
+                .insert_axis(0)?
+                .insert_axis(0)?; // [1024]
 
                 let xmin = cx - w / 2.;
                 let ymin = cy - h / 2.;
@@ -358,7 +430,7 @@ impl Moondream2 {
             }
         }
 
-        Ok((y_kpts, y_bboxes))
+        Ok((y_kpts, y_bboxes, interruption))
     }
 
     fn prepare_kv_cache(&mut self, image_embedding: &X) -> Result<Array<f32, IxDyn>> {
@@ -3,7 +3,9 @@ use anyhow::Result;
 use ndarray::{s, Axis};
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y,
+};
 
 /// OWL-ViT v2 model for open-vocabulary object detection.
 #[derive(Debug, Builder)]
@@ -90,6 +92,11 @@ impl OWLv2 {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("OWLv2", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("OWLv2", "inference", self.inference(ys)?);
         let ys = elapsed_module!("OWLv2", "postprocess", self.postprocess(ys)?);
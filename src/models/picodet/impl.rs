@@ -3,7 +3,9 @@ use anyhow::Result;
 use ndarray::Axis;
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct PicoDet {
@@ -62,6 +64,11 @@ impl PicoDet {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("PicoDet", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("PicoDet", "inference", self.inference(ys)?);
         let ys = elapsed_module!("PicoDet", "postprocess", self.postprocess(ys)?);
@@ -2,7 +2,8 @@ use aksr::Builder;
 use anyhow::Result;
 
 use crate::{
-    elapsed_module, Config, DType, Device, Engine, Image, Processor, Scale, Task, Version, Xs, X,
+    elapsed_module, run_in_batches, Config, DType, Device, Engine, Image, Processor, Scale, Task,
+    Version, Xs, X,
 };
 
 #[derive(Debug, Builder)]
@@ -13,7 +14,7 @@ pub struct BaseModelVisual {
     batch: usize,
     processor: Processor,
     spec: String,
-    name: &'static str,
+    name: String,
     device: Device,
     dtype: DType,
     task: Option<Task>,
@@ -68,11 +69,19 @@ impl BaseModelVisual {
         self.engine.run(xs)
     }
 
+    /// Encodes images in chunks of at most [`Engine::max_batch`], concatenated along the batch axis.
     pub fn encode(&mut self, xs: &[Image]) -> Result<X> {
-        let xs = elapsed_module!("BaseModelVisual", "visual-preprocess", self.preprocess(xs)?);
-        let xs = elapsed_module!("BaseModelVisual", "visual-inference", self.inference(xs)?);
+        let max_batch = self.engine.max_batch();
+        let ys = run_in_batches(xs, max_batch, |xs| {
+            let xs = elapsed_module!("BaseModelVisual", "visual-preprocess", self.preprocess(xs)?);
+            let xs = elapsed_module!("BaseModelVisual", "visual-inference", self.inference(xs)?);
+            Ok(vec![xs[0].to_owned()])
+        })?;
 
-        Ok(xs[0].to_owned())
+        match ys.len() {
+            1 => Ok(ys.into_iter().next().unwrap()),
+            _ => X::concat(&ys, 0),
+        }
     }
 }
 
@@ -82,7 +91,7 @@ pub struct BaseModelTextual {
     batch: usize,
     processor: Processor,
     spec: String,
-    name: &'static str,
+    name: String,
     device: Device,
     dtype: DType,
     task: Option<Task>,
@@ -3,7 +3,7 @@ use anyhow::Result;
 use ndarray::Axis;
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, Engine, Image, Prob, Processor, Xs, Y};
+use crate::{elapsed_module, forward_in_batches, Config, Engine, Image, Prob, Processor, Xs, Y};
 
 #[derive(Debug, Builder)]
 pub struct ImageClassifier {
@@ -66,6 +66,11 @@ impl ImageClassifier {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("ImageClassifier", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("ImageClassifier", "inference", self.inference(ys)?);
         let ys = elapsed_module!("ImageClassifier", "postprocess", self.postprocess(ys)?);
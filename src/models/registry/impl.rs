@@ -0,0 +1,702 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::{
+    models::{
+        Blip, DepthAnything, DepthPro, Florence2, GroundingDINO, ImageClassifier, MODNet,
+        MediaPipeSegmenter, Model, Moondream2, OWLv2, PicoDet, RTMPose, SLANet, Sapiens, SmolVLM,
+        Swin2SR, TrOCR, YOLOPv2, DB, RFDETR, RMBG, RTDETR, RTMO, SAM, SAM2, SVTR, YOLO,
+    },
+    Config, Scale, Version,
+};
+
+/// Builds a model from a committed [`Config`].
+pub type ModelBuilder = fn(Config) -> Result<Box<dyn Model>>;
+
+/// Resolves the variant part of a parametric id, e.g. `v8-n-det` for `yolo:v8-n-det`.
+pub type VariantResolver = fn(&str) -> Result<Config>;
+
+/// A model family: its id prefix, builder and `(variant, config)` presets.
+type Family<'a> = (&'a str, ModelBuilder, &'a [(&'a str, fn() -> Config)]);
+
+#[derive(Debug, Clone, Copy)]
+struct Preset {
+    config: fn() -> Config,
+    build: ModelBuilder,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds boxed [`Model`]s from string ids such as `"yolo:v8-n-det"` or `"rtdetr:v2-s"`.
+///
+/// An id is `family:variant`. It resolves, in order, to:
+/// - the preset registered under exactly that id;
+/// - the resolver registered for the family, if any (e.g. `yolo:<version>-<scale>-<task>`);
+/// - the preset of the family whose variant starts with the same `-`-separated tokens,
+///   preferring the one with the fewest extra tokens, so `rtdetr:v2-s` is `rtdetr:v2-s-coco`.
+///
+/// Ids are case-insensitive. Ambiguous or unknown ids fail with the candidates listed.
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    presets: BTreeMap<String, Preset>,
+    families: BTreeMap<String, (VariantResolver, ModelBuilder)>,
+}
+
+impl ModelRegistry {
+    /// Creates a registry with every model of the zoo registered.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register_builtins();
+        registry
+    }
+
+    /// Creates a registry with nothing registered.
+    pub fn empty() -> Self {
+        Self {
+            presets: BTreeMap::new(),
+            families: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a preset under `id`, replacing any previous one.
+    pub fn register(&mut self, id: &str, config: fn() -> Config, build: ModelBuilder) -> &mut Self {
+        self.presets
+            .insert(id.to_lowercase(), Preset { config, build });
+        self
+    }
+
+    /// Registers a resolver for ids of `family` that match no preset.
+    pub fn register_family(
+        &mut self,
+        family: &str,
+        resolve: VariantResolver,
+        build: ModelBuilder,
+    ) -> &mut Self {
+        self.families
+            .insert(family.to_lowercase(), (resolve, build));
+        self
+    }
+
+    /// Registered preset ids, sorted.
+    pub fn ids(&self) -> Vec<&str> {
+        self.presets.keys().map(|x| x.as_str()).collect()
+    }
+
+    /// Returns the uncommitted config of `id`.
+    pub fn config(&self, id: &str) -> Result<Config> {
+        Ok(self.resolve(id)?.0)
+    }
+
+    /// Builds the model of `id`.
+    pub fn build(&self, id: &str) -> Result<Box<dyn Model>> {
+        self.build_with(id, |config| config)
+    }
+
+    /// Builds the model of `id`, letting `f` adjust the config (e.g. device, dtype) before commit.
+    pub fn build_with<F>(&self, id: &str, f: F) -> Result<Box<dyn Model>>
+    where
+        F: FnOnce(Config) -> Config,
+    {
+        let (config, build) = self.resolve(id)?;
+        build(f(config).commit()?)
+    }
+
+    fn resolve(&self, id: &str) -> Result<(Config, ModelBuilder)> {
+        let id = id.trim().to_lowercase();
+        if let Some(preset) = self.presets.get(&id) {
+            return Ok(((preset.config)(), preset.build));
+        }
+
+        let (family, variant) = match id.split_once(':') {
+            Some((family, variant)) => (family.trim(), variant.trim()),
+            None => anyhow::bail!(
+                "Unknown model id `{}`. Expected `family:variant`, e.g. `yolo:v8-n-det`.",
+                id
+            ),
+        };
+        if let Some((resolve, build)) = self.families.get(family) {
+            return Ok((resolve(variant)?, *build));
+        }
+
+        let prefix = format!("{}:", family);
+        let variants: Vec<&str> = self
+            .presets
+            .keys()
+            .filter_map(|x| x.strip_prefix(&prefix))
+            .collect();
+        if variants.is_empty() {
+            anyhow::bail!(
+                "Unknown model family `{}`. Available: {}",
+                family,
+                self.families().join(", ")
+            );
+        }
+        match Self::match_variant(variant, &variants) {
+            Ok(x) => {
+                let preset = self.presets[&format!("{}{}", prefix, x)];
+                Ok(((preset.config)(), preset.build))
+            }
+            Err(xs) if xs.is_empty() => anyhow::bail!(
+                "Unknown model id `{}`. Available for `{}`: {}",
+                id,
+                family,
+                variants.join(", ")
+            ),
+            Err(xs) => anyhow::bail!("Ambiguous model id `{}`. Candidates: {}", id, xs.join(", ")),
+        }
+    }
+
+    fn families(&self) -> Vec<&str> {
+        let mut xs: Vec<&str> = self
+            .presets
+            .keys()
+            .map(|x| x.split_once(':').map_or(x.as_str(), |(x, _)| x))
+            .chain(self.families.keys().map(|x| x.as_str()))
+            .collect();
+        xs.sort_unstable();
+        xs.dedup();
+        xs
+    }
+
+    /// Finds the variant starting with the tokens of `variant` with the fewest extra tokens.
+    /// On failure, returns the tied candidates, empty if nothing matches.
+    fn match_variant<'a>(variant: &str, variants: &[&'a str]) -> Result<&'a str, Vec<&'a str>> {
+        let tokens: Vec<&str> = variant.split('-').collect();
+        let mut matched: Vec<(usize, &str)> = variants
+            .iter()
+            .filter_map(|x| {
+                let xs: Vec<&str> = x.split('-').collect();
+                xs.starts_with(&tokens).then_some((xs.len(), *x))
+            })
+            .collect();
+        matched.sort_unstable();
+        match matched[..] {
+            [] => Err(vec![]),
+            [(_, x)] => Ok(x),
+            [(n0, x), (n1, _), ..] if n0 < n1 => Ok(x),
+            [(n0, _), ..] => Err(matched
+                .iter()
+                .filter(|(n, _)| *n == n0)
+                .map(|(_, x)| *x)
+                .collect()),
+        }
+    }
+
+    /// Resolves `<version>-<scale>-<task>` of `yolo`, e.g. `v8-n-det` or `11-s-seg`.
+    fn yolo(variant: &str) -> Result<Config> {
+        let (version, scale, task) = match variant.split('-').collect::<Vec<_>>()[..] {
+            [version, scale, task] => (version, scale, task),
+            _ => anyhow::bail!(
+                "Invalid YOLO variant `{}`. Expected `<version>-<scale>-<task>`, e.g. `v8-n-det`.",
+                variant
+            ),
+        };
+        let config = match task {
+            "det" => Config::yolo_detect(),
+            "seg" => Config::yolo_segment(),
+            "pose" => Config::yolo_pose(),
+            "cls" => Config::yolo_classify(),
+            "obb" => Config::yolo_obb(),
+            x => anyhow::bail!(
+                "Invalid YOLO task `{}`. Expected one of: det, seg, pose, cls, obb.",
+                x
+            ),
+        };
+        let scale = match scale {
+            "n" | "s" | "m" | "l" | "x" => scale.parse::<Scale>()?,
+            x => anyhow::bail!(
+                "Invalid YOLO scale `{}`. Expected one of: n, s, m, l, x.",
+                x
+            ),
+        };
+
+        Ok(config
+            .with_version(version.parse::<Version>()?)
+            .with_scale(scale))
+    }
+
+    fn register_builtins(&mut self) {
+        let families: &[Family] = &[
+            (
+                "apisr",
+                |x| Ok(Box::new(Swin2SR::new(x)?)),
+                &[
+                    ("grl-4x", Config::apisr_grl_4x),
+                    ("rrdb-2x", Config::apisr_rrdb_2x),
+                ],
+            ),
+            (
+                "beit",
+                |x| Ok(Box::new(ImageClassifier::try_from(x)?)),
+                &[("base", Config::beit_base), ("large", Config::beit_large)],
+            ),
+            (
+                "ben2",
+                |x| Ok(Box::new(RMBG::new(x)?)),
+                &[("base", Config::ben2_base)],
+            ),
+            (
+                "blip",
+                |x| Ok(Box::new(Blip::new(x)?)),
+                &[("v1-base-caption", Config::blip_v1_base_caption)],
+            ),
+            (
+                "convnext",
+                |x| Ok(Box::new(ImageClassifier::try_from(x)?)),
+                &[
+                    ("v1-tiny", Config::convnext_v1_tiny),
+                    ("v1-small", Config::convnext_v1_small),
+                    ("v1-base", Config::convnext_v1_base),
+                    ("v1-large", Config::convnext_v1_large),
+                    ("v2-atto", Config::convnext_v2_atto),
+                    ("v2-femto", Config::convnext_v2_femto),
+                    ("v2-pico", Config::convnext_v2_pico),
+                    ("v2-nano", Config::convnext_v2_nano),
+                    ("v2-tiny", Config::convnext_v2_tiny),
+                    ("v2-small", Config::convnext_v2_small),
+                    ("v2-base", Config::convnext_v2_base),
+                    ("v2-large", Config::convnext_v2_large),
+                ],
+            ),
+            (
+                "d-fine",
+                |x| Ok(Box::new(RTDETR::new(x)?)),
+                &[
+                    ("n-coco", Config::d_fine_n_coco),
+                    ("s-coco", Config::d_fine_s_coco),
+                    ("m-coco", Config::d_fine_m_coco),
+                    ("l-coco", Config::d_fine_l_coco),
+                    ("x-coco", Config::d_fine_x_coco),
+                    ("s-coco-obj365", Config::d_fine_s_coco_obj365),
+                    ("m-coco-obj365", Config::d_fine_m_coco_obj365),
+                    ("l-coco-obj365", Config::d_fine_l_coco_obj365),
+                    ("x-coco-obj365", Config::d_fine_x_coco_obj365),
+                ],
+            ),
+            (
+                "db",
+                |x| Ok(Box::new(DB::new(x)?)),
+                &[
+                    ("ppocr-det-v3-ch", Config::ppocr_det_v3_ch),
+                    ("ppocr-det-v4-ch", Config::ppocr_det_v4_ch),
+                    ("ppocr-det-v4-server-ch", Config::ppocr_det_v4_server_ch),
+                    ("ppocr-det-v5-mobile", Config::ppocr_det_v5_mobile),
+                    ("ppocr-det-v5-server", Config::ppocr_det_v5_server),
+                    ("mobilenet-v3-large", Config::db_mobilenet_v3_large),
+                    ("mobilenet-v3-large-u8", Config::db_mobilenet_v3_large_u8),
+                    ("resnet34", Config::db_resnet34),
+                    ("resnet34-u8", Config::db_resnet34_u8),
+                    ("resnet50", Config::db_resnet50),
+                    ("resnet50-u8", Config::db_resnet50_u8),
+                ],
+            ),
+            (
+                "deim",
+                |x| Ok(Box::new(RTDETR::new(x)?)),
+                &[
+                    ("dfine-s-coco", Config::deim_dfine_s_coco),
+                    ("dfine-m-coco", Config::deim_dfine_m_coco),
+                    ("dfine-l-coco", Config::deim_dfine_l_coco),
+                    ("dfine-x-coco", Config::deim_dfine_x_coco),
+                ],
+            ),
+            (
+                "deit",
+                |x| Ok(Box::new(ImageClassifier::try_from(x)?)),
+                &[
+                    ("tiny-distill", Config::deit_tiny_distill),
+                    ("small-distill", Config::deit_small_distill),
+                    ("base-distill", Config::deit_base_distill),
+                ],
+            ),
+            (
+                "depth-anything",
+                |x| Ok(Box::new(DepthAnything::new(x)?)),
+                &[
+                    ("v1-small", Config::depth_anything_v1_small),
+                    ("v2-small", Config::depth_anything_v2_small),
+                ],
+            ),
+            (
+                "depth-pro",
+                |x| Ok(Box::new(DepthPro::new(x)?)),
+                &[("", Config::depth_pro)],
+            ),
+            (
+                "doclayout-yolo",
+                |x| Ok(Box::new(YOLO::new(x)?)),
+                &[("docstructbench", Config::doclayout_yolo_docstructbench)],
+            ),
+            (
+                "dwpose",
+                |x| Ok(Box::new(RTMPose::new(x)?)),
+                &[
+                    ("133-t", Config::dwpose_133_t),
+                    ("133-s", Config::dwpose_133_s),
+                    ("133-m", Config::dwpose_133_m),
+                    ("133-l", Config::dwpose_133_l),
+                    ("133-l-384", Config::dwpose_133_l_384),
+                ],
+            ),
+            (
+                "fast",
+                |x| Ok(Box::new(DB::new(x)?)),
+                &[
+                    ("tiny", Config::fast_tiny),
+                    ("small", Config::fast_small),
+                    ("base", Config::fast_base),
+                ],
+            ),
+            (
+                "fastsam",
+                |x| Ok(Box::new(YOLO::new(x)?)),
+                &[("s", Config::fastsam_s), ("x", Config::fastsam_x)],
+            ),
+            (
+                "fastvit",
+                |x| Ok(Box::new(ImageClassifier::try_from(x)?)),
+                &[
+                    ("t8", Config::fastvit_t8),
+                    ("t8-distill", Config::fastvit_t8_distill),
+                    ("t12", Config::fastvit_t12),
+                    ("t12-distill", Config::fastvit_t12_distill),
+                    ("s12", Config::fastvit_s12),
+                    ("s12-distill", Config::fastvit_s12_distill),
+                    ("sa12", Config::fastvit_sa12),
+                    ("sa12-distill", Config::fastvit_sa12_distill),
+                    ("sa24", Config::fastvit_sa24),
+                    ("sa24-distill", Config::fastvit_sa24_distill),
+                    ("sa36", Config::fastvit_sa36),
+                    ("sa36-distill", Config::fastvit_sa36_distill),
+                    ("ma36", Config::fastvit_ma36),
+                    ("ma36-distill", Config::fastvit_ma36_distill),
+                ],
+            ),
+            (
+                "florence2",
+                |x| Ok(Box::new(Florence2::new(x)?)),
+                &[("base", Config::florence2_base)],
+            ),
+            (
+                "grounding-dino",
+                |x| Ok(Box::new(GroundingDINO::new(x)?)),
+                &[
+                    ("tiny", Config::grounding_dino_tiny),
+                    ("base", Config::grounding_dino_base),
+                ],
+            ),
+            (
+                "linknet",
+                |x| Ok(Box::new(DB::new(x)?)),
+                &[
+                    ("r18", Config::linknet_r18),
+                    ("r34", Config::linknet_r34),
+                    ("r50", Config::linknet_r50),
+                ],
+            ),
+            (
+                "mediapipe",
+                |x| Ok(Box::new(MediaPipeSegmenter::new(x)?)),
+                &[
+                    ("selfie-segmenter", Config::mediapipe_selfie_segmentater),
+                    (
+                        "selfie-segmenter-landscape",
+                        Config::mediapipe_selfie_segmentater_landscape,
+                    ),
+                ],
+            ),
+            (
+                "mobileone",
+                |x| Ok(Box::new(ImageClassifier::try_from(x)?)),
+                &[
+                    ("s0", Config::mobileone_s0),
+                    ("s1", Config::mobileone_s1),
+                    ("s2", Config::mobileone_s2),
+                    ("s3", Config::mobileone_s3),
+                    ("s4-224x224", Config::mobileone_s4_224x224),
+                    ("s4-256x256", Config::mobileone_s4_256x256),
+                    ("s4-384x384", Config::mobileone_s4_384x384),
+                    ("s4-512x512", Config::mobileone_s4_512x512),
+                ],
+            ),
+            (
+                "modnet",
+                |x| Ok(Box::new(MODNet::new(x)?)),
+                &[("photographic", Config::modnet_photographic)],
+            ),
+            (
+                "moondream2",
+                |x| Ok(Box::new(Moondream2::new(x)?)),
+                &[
+                    ("0.5b", Config::moondream2_0_5b),
+                    ("2b", Config::moondream2_2b),
+                ],
+            ),
+            (
+                "owlv2",
+                |x| Ok(Box::new(OWLv2::new(x)?)),
+                &[
+                    ("base", Config::owlv2_base),
+                    ("base-ensemble", Config::owlv2_base_ensemble),
+                    ("base-ft", Config::owlv2_base_ft),
+                ],
+            ),
+            (
+                "picodet",
+                |x| Ok(Box::new(PicoDet::new(x)?)),
+                &[
+                    ("l-coco", Config::picodet_l_coco),
+                    ("layout-1x", Config::picodet_layout_1x),
+                    ("l-layout-3cls", Config::picodet_l_layout_3cls),
+                    ("l-layout-17cls", Config::picodet_l_layout_17cls),
+                ],
+            ),
+            (
+                "rfdetr",
+                |x| Ok(Box::new(RFDETR::new(x)?)),
+                &[
+                    ("base", Config::rfdetr_base),
+                    ("large", Config::rfdetr_large),
+                ],
+            ),
+            (
+                "rmbg",
+                |x| Ok(Box::new(RMBG::new(x)?)),
+                &[("1.4", Config::rmbg1_4), ("2.0", Config::rmbg2_0)],
+            ),
+            (
+                "rtdetr",
+                |x| Ok(Box::new(RTDETR::new(x)?)),
+                &[
+                    ("v1-r18vd-coco", Config::rtdetr_v1_r18vd_coco),
+                    ("v2-s-coco", Config::rtdetr_v2_s_coco),
+                    ("v2-ms-coco", Config::rtdetr_v2_ms_coco),
+                    ("v2-m-coco", Config::rtdetr_v2_m_coco),
+                    ("v2-l-coco", Config::rtdetr_v2_l_coco),
+                    ("v2-x-coco", Config::rtdetr_v2_x_coco),
+                ],
+            ),
+            (
+                "rtmo",
+                |x| Ok(Box::new(RTMO::new(x)?)),
+                &[
+                    ("t", Config::rtmo_t),
+                    ("s", Config::rtmo_s),
+                    ("m", Config::rtmo_m),
+                    ("l", Config::rtmo_l),
+                ],
+            ),
+            (
+                "rtmpose",
+                |x| Ok(Box::new(RTMPose::new(x)?)),
+                &[
+                    ("17-t", Config::rtmpose_17_t),
+                    ("17-s", Config::rtmpose_17_s),
+                    ("17-m", Config::rtmpose_17_m),
+                    ("17-l", Config::rtmpose_17_l),
+                    ("17-l-384", Config::rtmpose_17_l_384),
+                    ("17-x", Config::rtmpose_17_x),
+                    ("26-t", Config::rtmpose_26_t),
+                    ("26-s", Config::rtmpose_26_s),
+                    ("26-m", Config::rtmpose_26_m),
+                    ("26-m-384", Config::rtmpose_26_m_384),
+                    ("26-l", Config::rtmpose_26_l),
+                    ("26-l-384", Config::rtmpose_26_l_384),
+                    ("26-x", Config::rtmpose_26_x),
+                ],
+            ),
+            (
+                "rtmw",
+                |x| Ok(Box::new(RTMPose::new(x)?)),
+                &[
+                    ("133-m", Config::rtmw_133_m),
+                    ("133-m-384", Config::rtmw_133_m_384),
+                    ("133-l", Config::rtmw_133_l),
+                    ("133-x", Config::rtmw_133_x),
+                ],
+            ),
+            (
+                "sam",
+                |x| Ok(Box::new(SAM::new(x)?)),
+                &[
+                    ("v1-base", Config::sam_v1_base),
+                    ("mobile-tiny", Config::mobile_sam_tiny),
+                    ("hq-tiny", Config::sam_hq_tiny),
+                    ("edge-3x", Config::edge_sam_3x),
+                ],
+            ),
+            (
+                "sam2",
+                |x| Ok(Box::new(SAM::new(x)?)),
+                &[
+                    ("tiny", Config::sam2_tiny),
+                    ("small", Config::sam2_small),
+                    ("base-plus", Config::sam2_base_plus),
+                ],
+            ),
+            (
+                "sam2.1",
+                |x| Ok(Box::new(SAM2::new(x)?)),
+                &[
+                    ("tiny", Config::sam2_1_tiny),
+                    ("small", Config::sam2_1_small),
+                    ("base-plus", Config::sam2_1_base_plus),
+                    ("large", Config::sam2_1_large),
+                ],
+            ),
+            (
+                "sapiens",
+                |x| Ok(Box::new(Sapiens::new(x)?)),
+                &[("seg-0.3b", Config::sapiens_seg_0_3b)],
+            ),
+            (
+                "slanet",
+                |x| Ok(Box::new(SLANet::new(x)?)),
+                &[("lcnet-v2-mobile-ch", Config::slanet_lcnet_v2_mobile_ch)],
+            ),
+            (
+                "smolvlm",
+                |x| Ok(Box::new(SmolVLM::new(x)?)),
+                &[
+                    ("256m", Config::smolvlm_256m),
+                    ("500m", Config::smolvlm_500m),
+                ],
+            ),
+            (
+                "svtr",
+                |x| Ok(Box::new(SVTR::new(x)?)),
+                &[
+                    ("ppocr-rec-v3-ch", Config::ppocr_rec_v3_ch),
+                    ("ppocr-rec-v4-ch", Config::ppocr_rec_v4_ch),
+                    ("ppocr-rec-v3-en", Config::ppocr_rec_v3_en),
+                    ("ppocr-rec-v4-en", Config::ppocr_rec_v4_en),
+                    ("ppocr-rec-v4-server-ch", Config::ppocr_rec_v4_server_ch),
+                    ("ppocr-rec-v5-mobile", Config::ppocr_rec_v5_mobile),
+                    ("ppocr-rec-v5-server", Config::ppocr_rec_v5_server),
+                    ("v2-server-ch", Config::svtr_v2_server_ch),
+                    ("v2-teacher-ch", Config::svtr_v2_teacher_ch),
+                    ("v2-student-ch", Config::svtr_v2_student_ch),
+                    ("repsvtr-ch", Config::repsvtr_ch),
+                ],
+            ),
+            (
+                "swin2sr",
+                |x| Ok(Box::new(Swin2SR::new(x)?)),
+                &[
+                    ("lightweight-x2-64", Config::swin2sr_lightweight_x2_64),
+                    ("classical-x2-64", Config::swin2sr_classical_x2_64),
+                    ("classical-x4-64", Config::swin2sr_classical_x4_64),
+                    (
+                        "realworld-x4-64-bsrgan-psnr",
+                        Config::swin2sr_realworld_x4_64_bsrgan_psnr,
+                    ),
+                    ("compressed-x4-48", Config::swin2sr_compressed_x4_48),
+                ],
+            ),
+            (
+                "trocr",
+                |x| Ok(Box::new(TrOCR::new(x)?)),
+                &[
+                    ("small-printed", Config::trocr_small_printed),
+                    ("base-printed", Config::trocr_base_printed),
+                    ("small-handwritten", Config::trocr_small_handwritten),
+                    ("base-handwritten", Config::trocr_base_handwritten),
+                ],
+            ),
+            (
+                "ultralytics-rtdetr",
+                |x| Ok(Box::new(YOLO::new(x)?)),
+                &[
+                    ("l", Config::ultralytics_rtdetr_l),
+                    ("x", Config::ultralytics_rtdetr_x),
+                ],
+            ),
+            (
+                "yoloe",
+                |x| Ok(Box::new(YOLO::new(x)?)),
+                &[
+                    ("v8s-seg-pf", Config::yoloe_v8s_seg_pf),
+                    ("v8m-seg-pf", Config::yoloe_v8m_seg_pf),
+                    ("v8l-seg-pf", Config::yoloe_v8l_seg_pf),
+                    ("11s-seg-pf", Config::yoloe_11s_seg_pf),
+                    ("11m-seg-pf", Config::yoloe_11m_seg_pf),
+                    ("11l-seg-pf", Config::yoloe_11l_seg_pf),
+                ],
+            ),
+            (
+                "yolop",
+                |x| Ok(Box::new(YOLOPv2::new(x)?)),
+                &[
+                    ("v2-480x800", Config::yolop_v2_480x800),
+                    ("v2-736x1280", Config::yolop_v2_736x1280),
+                ],
+            ),
+        ];
+
+        for &(family, build, variants) in families {
+            for &(variant, config) in variants {
+                let id = if variant.is_empty() {
+                    family.to_string()
+                } else {
+                    format!("{}:{}", family, variant)
+                };
+                self.register(&id, config, build);
+            }
+        }
+        self.register_family("yolo", Self::yolo, |x| Ok(Box::new(YOLO::new(x)?)));
+    }
+}
+
+#[cfg(test)]
+mod tests_model_registry {
+    use super::ModelRegistry;
+
+    #[test]
+    fn test_match_variant() {
+        let variants = [
+            "v2-s-coco",
+            "v2-ms-coco",
+            "v2-m-coco",
+            "s-coco",
+            "s-coco-obj365",
+        ];
+        assert_eq!(
+            ModelRegistry::match_variant("v2-s", &variants),
+            Ok("v2-s-coco")
+        );
+        assert_eq!(
+            ModelRegistry::match_variant("v2-m", &variants),
+            Ok("v2-m-coco")
+        );
+        assert_eq!(ModelRegistry::match_variant("s", &variants), Ok("s-coco"));
+        assert_eq!(
+            ModelRegistry::match_variant("s-coco-obj365", &variants),
+            Ok("s-coco-obj365")
+        );
+        assert_eq!(ModelRegistry::match_variant("v3", &variants), Err(vec![]));
+
+        let variants = ["v2-s-coco", "v2-s-obj365"];
+        assert_eq!(
+            ModelRegistry::match_variant("v2-s", &variants),
+            Err(vec!["v2-s-coco", "v2-s-obj365"])
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let registry = ModelRegistry::new();
+        assert!(registry.config("rtdetr:v2-s").is_ok());
+        assert!(registry.config("RTDETR:v2-s-coco").is_ok());
+        assert!(registry.config("depth-pro").is_ok());
+        assert!(registry.config("yolo:v8-n-det").is_ok());
+        assert!(registry.config("yolo:v8-n").is_err());
+        assert!(registry.config("yolo:v8-q-det").is_err());
+        assert!(registry.config("rtdetr:v3").is_err());
+        assert!(registry.config("unknown:v1").is_err());
+        assert!(registry.config("rtdetr").is_err());
+    }
+}
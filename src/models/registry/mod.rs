@@ -0,0 +1,5 @@
+mod r#impl;
+mod model;
+
+pub use model::*;
+pub use r#impl::*;
@@ -0,0 +1,294 @@
+use anyhow::Result;
+
+use crate::{
+    models::{
+        Blip, DepthAnything, DepthPro, Dinomaly, Florence2, GroundingDINO, ImageClassifier, MODNet,
+        MediaPipeSegmenter, Moondream2, OWLv2, PicoDet, RTMPose, SLANet, SamPrompt, Sapiens,
+        SmolVLM, Swin2SR, TrOCR, UniNet, YOLOPv2, DB, GLASS, RFDETR, RMBG, RTDETR, RTMO, SAM, SAM2,
+        SVTR, YOLO,
+    },
+    Hbb, Image, Task, Y,
+};
+
+/// Inputs of [`Model::run`].
+///
+/// Images are always required; the other fields are only read by the models that take them,
+/// e.g. `task` by Florence2 and Moondream2, `text` by BLIP and SmolVLM, `prompts` by SAM
+/// and SAM2, `hbbs` by RTMPose.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ModelInput<'a> {
+    pub images: &'a [Image],
+    pub text: Option<&'a str>,
+    pub task: Option<&'a Task>,
+    pub prompts: &'a [SamPrompt],
+    pub hbbs: Option<&'a [Hbb]>,
+}
+
+impl<'a> ModelInput<'a> {
+    pub fn new(images: &'a [Image]) -> Self {
+        Self {
+            images,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_text(mut self, text: &'a str) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    pub fn with_task(mut self, task: &'a Task) -> Self {
+        self.task = Some(task);
+        self
+    }
+
+    pub fn with_prompts(mut self, prompts: &'a [SamPrompt]) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn with_hbbs(mut self, hbbs: &'a [Hbb]) -> Self {
+        self.hbbs = Some(hbbs);
+        self
+    }
+
+    fn try_task(&self, model: &str) -> Result<&'a Task> {
+        self.task
+            .ok_or_else(|| anyhow::anyhow!("{} requires a task in `ModelInput`.", model))
+    }
+
+    fn try_text(&self, model: &str) -> Result<&'a str> {
+        self.text
+            .ok_or_else(|| anyhow::anyhow!("{} requires a text in `ModelInput`.", model))
+    }
+}
+
+impl<'a> From<&'a [Image]> for ModelInput<'a> {
+    fn from(images: &'a [Image]) -> Self {
+        Self::new(images)
+    }
+}
+
+impl<'a> From<&'a Vec<Image>> for ModelInput<'a> {
+    fn from(images: &'a Vec<Image>) -> Self {
+        Self::new(images)
+    }
+}
+
+/// Common interface of the models in the zoo, so they can be used behind a `Box<dyn Model>`.
+///
+/// Embedding models (CLIP, DINOv2) return tensors rather than [`Y`] and do not implement it.
+pub trait Model {
+    /// Model name, usually the spec of its engine.
+    fn name(&self) -> &str;
+
+    /// Task the model performs, `None` when it depends on the inputs or has no [`Task`] variant.
+    fn task(&self) -> Option<Task>;
+
+    /// Runs the full preprocess-inference-postprocess pipeline.
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>>;
+
+    /// One-line description of the model.
+    fn summary(&self) -> String {
+        match self.task() {
+            Some(task) => format!("{} ({})", self.name(), task),
+            None => self.name().to_string(),
+        }
+    }
+}
+
+/// Implements [`Model`] for models whose `forward` only takes images.
+macro_rules! impl_model {
+    ($ty:ty, $task:expr) => {
+        impl Model for $ty {
+            fn name(&self) -> &str {
+                self.spec()
+            }
+
+            fn task(&self) -> Option<Task> {
+                $task
+            }
+
+            fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+                self.forward(inputs.images)
+            }
+        }
+    };
+    ($ty:ty, $task:expr, $name:literal) => {
+        impl Model for $ty {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn task(&self) -> Option<Task> {
+                $task
+            }
+
+            fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+                self.forward(inputs.images)
+            }
+        }
+    };
+}
+
+impl_model!(DB, None);
+impl_model!(DepthAnything, Some(Task::DepthEstimation));
+impl_model!(DepthPro, Some(Task::DepthEstimation));
+impl_model!(GroundingDINO, Some(Task::ObjectDetection));
+impl_model!(ImageClassifier, Some(Task::ImageClassification));
+impl_model!(MediaPipeSegmenter, Some(Task::SemanticSegmentation));
+impl_model!(MODNet, Some(Task::SemanticSegmentation));
+impl_model!(OWLv2, Some(Task::ObjectDetection));
+impl_model!(PicoDet, Some(Task::ObjectDetection));
+impl_model!(RFDETR, Some(Task::ObjectDetection));
+impl_model!(RMBG, Some(Task::SemanticSegmentation));
+impl_model!(RTDETR, Some(Task::ObjectDetection));
+impl_model!(RTMO, Some(Task::KeypointsDetection));
+impl_model!(SLANet, None);
+impl_model!(SVTR, Some(Task::Ocr));
+impl_model!(Swin2SR, Some(Task::SuperResolution));
+impl_model!(YOLOPv2, Some(Task::ObjectDetection));
+impl_model!(Dinomaly, None, "dinomaly");
+impl_model!(GLASS, None, "glass");
+impl_model!(UniNet, None, "uninet");
+impl_model!(TrOCR, Some(Task::Ocr), "trocr");
+
+impl Model for YOLO {
+    fn name(&self) -> &str {
+        self.spec()
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(YOLO::task(self).clone())
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        self.forward(inputs.images)
+    }
+}
+
+impl Model for Sapiens {
+    fn name(&self) -> &str {
+        self.spec()
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(Sapiens::task(self).clone())
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        self.forward(inputs.images)
+    }
+}
+
+impl Model for RTMPose {
+    fn name(&self) -> &str {
+        self.spec()
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(Task::KeypointsDetection)
+    }
+
+    /// Boxes in `hbbs` belong to a single image; without boxes, each image is one instance.
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        if inputs.hbbs.is_some() && inputs.images.len() != 1 {
+            anyhow::bail!(
+                "RTMPose takes boxes for a single image, but got {} images.",
+                inputs.images.len()
+            );
+        }
+        inputs
+            .images
+            .iter()
+            .map(|x| self.forward(x, inputs.hbbs))
+            .collect()
+    }
+}
+
+impl Model for SAM {
+    fn name(&self) -> &str {
+        self.spec()
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(Task::InstanceSegmentation)
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        self.forward(inputs.images, inputs.prompts)
+    }
+}
+
+impl Model for SAM2 {
+    fn name(&self) -> &str {
+        self.spec()
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(Task::InstanceSegmentation)
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        self.forward(inputs.images, inputs.prompts)
+    }
+}
+
+impl Model for Blip {
+    fn name(&self) -> &str {
+        "blip"
+    }
+
+    fn task(&self) -> Option<Task> {
+        Some(Task::Caption(0))
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        self.forward(inputs.images, inputs.text)
+    }
+}
+
+impl Model for SmolVLM {
+    fn name(&self) -> &str {
+        "smolvlm"
+    }
+
+    fn task(&self) -> Option<Task> {
+        None
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        let text = inputs.try_text("SmolVLM")?;
+        self.forward(inputs.images, text)
+    }
+}
+
+impl Model for Florence2 {
+    fn name(&self) -> &str {
+        "florence2"
+    }
+
+    fn task(&self) -> Option<Task> {
+        None
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        let task = inputs.try_task("Florence2")?;
+        self.forward(inputs.images, task)
+    }
+}
+
+impl Model for Moondream2 {
+    fn name(&self) -> &str {
+        "moondream2"
+    }
+
+    fn task(&self) -> Option<Task> {
+        None
+    }
+
+    fn run(&mut self, inputs: ModelInput) -> Result<Vec<Y>> {
+        let task = inputs.try_task("Moondream2")?;
+        self.forward(inputs.images, task)
+    }
+}
@@ -3,7 +3,9 @@ use anyhow::Result;
 use ndarray::{s, Axis};
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Processor, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Processor, Xs, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct RFDETR {
@@ -55,6 +57,11 @@ impl RFDETR {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("RFDETR", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("RFDETR", "inference", self.inference(ys)?);
         let ys = elapsed_module!("RFDETR", "postprocess", self.postprocess(ys)?);
@@ -3,7 +3,9 @@ use anyhow::Result;
 use ndarray::{s, Axis};
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Processor, Xs, X, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct RTDETR {
@@ -46,6 +48,7 @@ impl RTDETR {
 
     fn preprocess(&mut self, xs: &[Image]) -> Result<Xs> {
         let x1 = self.processor.process_images(xs)?;
+        self.batch = xs.len(); // update
         let x2 = X::from(vec![self.height as f32, self.width as f32])
             .insert_axis(0)?
             .repeat(0, self.batch)?;
@@ -60,6 +63,11 @@ impl RTDETR {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("RTDETR", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("RTDETR", "inference", self.inference(ys)?);
         let ys = elapsed_module!("RTDETR", "postprocess", self.postprocess(ys)?);
@@ -3,7 +3,10 @@ use anyhow::Result;
 use ndarray::Axis;
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Hbb, Image, Keypoint, Processor, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, Keypoint, Processor,
+    Xs, Y,
+};
 
 #[derive(Builder, Debug)]
 pub struct RTMO {
@@ -59,6 +62,11 @@ impl RTMO {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("RTMO", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("RTMO", "inference", self.inference(ys)?);
         let ys = elapsed_module!("RTMO", "postprocess", self.postprocess(ys)?);
@@ -132,7 +132,10 @@ impl RTMPose {
             self.postprocess(ys, centers_and_scales)?
         });
 
-        Ok(y)
+        Ok(match x.frame() {
+            Some(frame) => y.with_frame(frame.clone()),
+            None => y,
+        })
     }
 
     fn postprocess(&mut self, xs: Xs, centers_and_scales: CentersAndScales) -> Result<Y> {
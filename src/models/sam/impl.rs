@@ -2,15 +2,16 @@ use aksr::Builder;
 use anyhow::Result;
 use ndarray::{s, Axis};
 use rand::{prelude::*, rng};
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 use crate::{
-    elapsed_module, Config, DynConf, Engine, Image, Mask, Ops, Polygon, Processor, SamPrompt, Xs,
-    X, Y,
+    elapsed_module, keep_frames, Config, DynConf, Engine, Image, Mask, Ops, Polygon, Processor,
+    SamPrompt, Xs, X, Y,
 };
 
 /// SAM model variants for different use cases.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SamKind {
     /// Original SAM model
     Sam,
@@ -117,7 +118,7 @@ impl SAM {
         let ys = elapsed_module!("SAM", "encode", self.encode(xs)?);
         let ys = elapsed_module!("SAM", "decode", self.decode(&ys, prompts)?);
 
-        Ok(ys)
+        Ok(keep_frames(xs, ys))
     }
 
     /// Encodes input images into image embeddings.
@@ -3,7 +3,8 @@ use anyhow::Result;
 use ndarray::{s, Axis};
 
 use crate::{
-    elapsed_module, Config, DynConf, Engine, Image, Mask, Ops, Processor, SamPrompt, Xs, X, Y,
+    elapsed_module, keep_frames, Config, DynConf, Engine, Image, Mask, Ops, Processor, SamPrompt,
+    Xs, X, Y,
 };
 
 /// SAM2 (Segment Anything Model 2.1) for advanced image segmentation.
@@ -64,7 +65,7 @@ impl SAM2 {
         let ys = elapsed_module!("SAM2", "encode", self.encode(xs)?);
         let ys = elapsed_module!("SAM2", "decode", self.decode(&ys, prompts)?);
 
-        Ok(ys)
+        Ok(keep_frames(xs, ys))
     }
 
     /// Encodes input images into hierarchical feature representations.
@@ -2,7 +2,10 @@ use aksr::Builder;
 use anyhow::Result;
 use ndarray::{s, Array2, Axis};
 
-use crate::{elapsed_module, Config, Engine, Image, Mask, Ops, Polygon, Processor, Task, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, Engine, Image, Mask, Ops, Polygon, Processor, Task,
+    Xs, Y,
+};
 
 #[derive(Builder, Debug)]
 pub struct Sapiens {
@@ -52,6 +55,11 @@ impl Sapiens {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("Sapiens", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("Sapiens", "inference", self.inference(ys)?);
         let ys = elapsed_module!("Sapiens", "postprocess", {
@@ -2,7 +2,10 @@ use aksr::Builder;
 use anyhow::Result;
 use ndarray::{s, Axis};
 
-use crate::{elapsed_module, models::BaseModelVisual, Config, Image, Keypoint, Text, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, models::BaseModelVisual, Config, Image, Keypoint, Text, Xs,
+    Y,
+};
 
 #[derive(Builder, Debug)]
 pub struct SLANet {
@@ -31,6 +34,11 @@ impl SLANet {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.base.engine().max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("SLANet", "preprocess", self.base.preprocess(xs)?);
         let ys = elapsed_module!("SLANet", "inference", self.base.inference(ys)?);
         let ys = elapsed_module!("SLANet", "postprocess", self.postprocess(ys)?);
@@ -1,9 +1,12 @@
 use aksr::Builder;
 use anyhow::Result;
 use image::GenericImageView;
-use ndarray::s;
+use ndarray::{s, Array};
 
-use crate::{Config, Engine, Image, LogitsSampler, Processor, Scale, Xs, X, Y};
+use crate::{
+    keep_frames, run_or_break, CancelToken, Config, Engine, Image, Interruption, LogitsSampler,
+    Processor, Scale, XAny, Xs, X, Y,
+};
 
 #[derive(Debug, Builder)]
 pub struct SmolVLM {
@@ -28,6 +31,8 @@ pub struct SmolVLM {
     width: usize,
     height: usize,
     processor: Processor,
+    #[args(except(setter))]
+    cancel_token: CancelToken,
 }
 
 impl SmolVLM {
@@ -85,20 +90,38 @@ impl SmolVLM {
             height,
             width,
             processor,
+            cancel_token: CancelToken::default(),
         })
     }
 
+    /// Sets the token checked between generation steps, also passed to every engine.
+    pub fn with_cancel_token(mut self, x: CancelToken) -> Self {
+        self.vision = self.vision.with_cancel_token(x.clone());
+        self.text_embed = self.text_embed.with_cancel_token(x.clone());
+        self.decoder = self.decoder.with_cancel_token(x.clone());
+        self.cancel_token = x;
+        self
+    }
+
     pub fn forward(&mut self, images: &[Image], text: &str) -> Result<Vec<Y>> {
         let mut ys: Vec<Y> = Vec::new();
         for image in images.iter() {
-            let y = self.generate_one(image, text)?;
-            ys.push(Y::default().with_texts(&[y.into()]));
+            let (generated, interruption) = self.generate_one(image, text)?;
+            let y = Y::default().with_texts(&[generated.into()]);
+            ys.push(match interruption {
+                Some(x) => y.with_interruption(x),
+                None => y,
+            });
         }
 
-        Ok(ys)
+        Ok(keep_frames(images, ys))
     }
 
-    fn generate_one(&mut self, image: &Image, text: &str) -> Result<String> {
+    fn generate_one(
+        &mut self,
+        image: &Image,
+        text: &str,
+    ) -> Result<(String, Option<Interruption>)> {
         let bs = 1; // TODO
 
         // patches and pixel_attention_mask
@@ -108,7 +131,13 @@ impl SmolVLM {
 
         // input ids
         let prompt = self.image_prompt_string(nw_nh, text);
-        let mut input_ids: Vec<f32> = self.processor.encode_text_ids(&prompt, true)?;
+        let mut input_ids: Vec<i64> = self
+            .processor
+            .encode_text(&prompt, true)?
+            .get_ids()
+            .iter()
+            .map(|&x| x as i64)
+            .collect();
 
         // position ids
         let mut position_ids = X::from(
@@ -127,28 +156,41 @@ impl SmolVLM {
         // generate
         let logits_sampler = LogitsSampler::new();
         let mut token_ids: Vec<u32> = vec![];
+        let mut interruption = None;
         for ii in 0..self.max_length {
+            if let Some(x) = self.cancel_token.interrupted() {
+                interruption = Some(x);
+                break;
+            }
+
             // inputs embeds
-            let input_ids_x = X::from(input_ids.clone()).insert_axis(0)?;
-            let mut inputs_embeds = self.text_embed.run(input_ids_x.clone().into())?[0].clone();
+            let input_ids_x = Array::from_shape_vec((1, input_ids.len()), input_ids.clone())?;
+            let mut inputs_embeds = run_or_break!(
+                self.text_embed.run_any(XAny::from(input_ids_x.into_dyn())),
+                interruption
+            )[0]
+            .to_x();
 
             // encode image and merge
             if ii == 0 {
-                let image_features = self.vision.run(Xs::from(vec![
-                    patches.clone(),
-                    pixel_attention_mask.clone(),
-                ]))?[0]
-                    .clone();
+                let image_features = run_or_break!(
+                    self.vision.run(Xs::from(vec![
+                        patches.clone(),
+                        pixel_attention_mask.clone(),
+                    ])),
+                    interruption
+                )[0]
+                .clone();
                 let dims = image_features.dim();
                 let image_features = image_features.to_shape((dims[0] * dims[1], dims[2]))?;
 
                 // merge
                 let mut r = 0;
-                for (i, &token_id) in input_ids_x.indexed_iter() {
-                    if token_id == self.image_token_id as f32 {
+                for (i, &token_id) in input_ids.iter().enumerate() {
+                    if token_id == self.image_token_id as i64 {
                         inputs_embeds
                             .0
-                            .slice_mut(s![0, i[1], ..])
+                            .slice_mut(s![0, i, ..])
                             .assign(&image_features.slice(s![r, ..]));
                         r += 1;
                     }
@@ -158,7 +200,7 @@ impl SmolVLM {
             // inputs
             let mut xs = vec![
                 inputs_embeds.clone(),
-                X::ones_like(&input_ids_x),
+                X::ones(&[1, input_ids.len()]),
                 position_ids.clone(),
             ];
             for i in 0..self.num_hidden_layers {
@@ -167,7 +209,7 @@ impl SmolVLM {
             }
 
             // decode
-            let decoder_outputs = self.decoder.run(xs.into())?;
+            let decoder_outputs = run_or_break!(self.decoder.run(xs.into()), interruption);
             let logits = &decoder_outputs[0];
             past_key_values = (1..decoder_outputs.len())
                 .step_by(2)
@@ -190,7 +232,7 @@ impl SmolVLM {
             }
 
             // update
-            input_ids = vec![token_id as f32];
+            input_ids = vec![token_id as i64];
             position_ids = X::from(
                 position_ids
                     .slice(s![.., -1..])
@@ -203,7 +245,7 @@ impl SmolVLM {
         // decode tokens
         let text = self.processor.decode_tokens(&token_ids, true)?;
 
-        Ok(text)
+        Ok((text, interruption))
     }
 
     fn image_prompt_string(&self, nw_nh: (u32, u32), text: &str) -> String {
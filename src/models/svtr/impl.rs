@@ -3,7 +3,9 @@ use anyhow::Result;
 use ndarray::Axis;
 use rayon::prelude::*;
 
-use crate::{elapsed_module, Config, DynConf, Engine, Image, Processor, Text, Xs, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Image, Processor, Text, Xs, Y,
+};
 
 /// SVTR (Scene Text Recognition) model for text recognition.
 #[derive(Builder, Debug)]
@@ -55,6 +57,11 @@ impl SVTR {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("SVTR", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("SVTR", "inference", self.inference(ys)?);
         let ys = elapsed_module!("SVTR", "postprocess", self.postprocess(ys)?);
@@ -66,9 +73,20 @@ impl SVTR {
         let ys: Vec<Y> = xs[0]
             .axis_iter(Axis(0))
             .into_par_iter()
-            .map(|preds| {
+            .enumerate()
+            .map(|(idx, preds)| {
+                // skip the steps of the width padding of bucketed inputs
+                let info = &self.processor.images_transform_info[idx];
+                let steps = preds.len_of(Axis(0));
+                let n = if info.width_pad > 0. {
+                    let n = (steps as f32 * (1. - info.width_pad / info.width_dst as f32)).round();
+                    (n as usize).clamp(1, steps)
+                } else {
+                    steps
+                };
                 let mut preds: Vec<_> = preds
                     .axis_iter(Axis(0))
+                    .take(n)
                     .filter_map(|x| x.into_iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)))
                     .collect();
 
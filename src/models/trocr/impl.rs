@@ -4,7 +4,10 @@ use ndarray::{s, Axis};
 use rayon::prelude::*;
 use std::str::FromStr;
 
-use crate::{elapsed_module, Config, Engine, Image, LogitsSampler, Processor, Scale, Xs, X, Y};
+use crate::{
+    elapsed_module, forward_in_batches, Config, Engine, Image, LogitsSampler, Processor, Scale, Xs,
+    X, Y,
+};
 
 /// TrOCR model variants for different text types.
 #[derive(Debug, Copy, Clone)]
@@ -150,6 +153,11 @@ impl TrOCR {
     /// # Errors
     /// Returns an error if any step in the forward pass fails.
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.encoder.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let encoder_hidden_states = elapsed_module!("TrOCR", "encode", self.encode(xs)?);
         let generated = elapsed_module!("TrOCR", "generate", {
             self.generate(&encoder_hidden_states)?
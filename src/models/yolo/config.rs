@@ -7,14 +7,13 @@ use crate::{
 impl Config {
     /// Creates a base YOLO configuration with common settings.
     ///
-    /// Sets up default input dimensions (640x640) and image processing parameters.
+    /// Sets up default input dimensions and image processing parameters. The input height and
+    /// width are left to the model's `imgsz` metadata, falling back to 640x640.
     pub fn yolo() -> Self {
         Self::default()
             .with_name("yolo")
             .with_model_ixx(0, 0, 1.into())
             .with_model_ixx(0, 1, 3.into())
-            .with_model_ixx(0, 2, 640.into())
-            .with_model_ixx(0, 3, 640.into())
             .with_resize_mode(ResizeMode::FitAdaptive)
             .with_resize_filter("CatmullRom")
     }
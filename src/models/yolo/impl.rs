@@ -1,12 +1,14 @@
 use aksr::Builder;
 use anyhow::Result;
-use log::{error, info};
+use log::{error, info, warn};
 use ndarray::{s, Array, Axis};
 use rayon::prelude::*;
 use regex::Regex;
+use std::sync::Arc;
 
 use crate::{
-    elapsed_module,
+    core::onnx,
+    elapsed_module, forward_in_batches,
     models::{BoxType, YOLOPredsFormat},
     Config, DynConf, Engine, Hbb, Image, Keypoint, Mask, NmsOps, Obb, Ops, Prob, Processor, Task,
     Version, Xs, Y,
@@ -58,31 +60,30 @@ impl YOLO {
     /// - Loading the ONNX model file
     /// - Setting up input dimensions and processing parameters
     /// - Configuring task-specific settings and output formats
+    ///
+    /// Task, version, class names and input size left unset in the config are read from the
+    /// Ultralytics metadata (`task`, `description`, `names`, `imgsz`, ...) when available.
     pub fn new(config: Config) -> Result<Self> {
-        let engine = Engine::try_from_config(&config.model)?;
+        let mut engine = Engine::from_config(&config.model);
+        let proto = engine.load_proto()?;
+        let stride = Self::fetch_stride_from_onnx(&proto).unwrap_or(32);
+        let config = Self::fill_imgsz_from_onnx(config, &proto, stride);
+        engine.iiixs = config.model.iiixs.clone();
+        let engine = engine.build_with_proto(Arc::new(proto))?;
+        let config = Self::fill_config_from_onnx(config, &engine);
         let (batch, height, width, spec) = (
             engine.batch().opt(),
             engine.try_height().unwrap_or(&640.into()).opt(),
             engine.try_width().unwrap_or(&640.into()).opt(),
             engine.spec().to_owned(),
         );
-        let task: Option<Task> = match &config.task {
-            Some(task) => Some(task.clone()),
-            None => match engine.try_fetch("task") {
-                Some(x) => match x.as_str() {
-                    "classify" => Some(Task::ImageClassification),
-                    "detect" => Some(Task::ObjectDetection),
-                    "pose" => Some(Task::KeypointsDetection),
-                    "segment" => Some(Task::InstanceSegmentation),
-                    "obb" => Some(Task::OrientedObjectDetection),
-                    x => {
-                        error!("Unsupported YOLO Task: {}", x);
-                        None
-                    }
-                },
-                None => None,
-            },
-        };
+        if height % stride != 0 || width % stride != 0 {
+            warn!(
+                "Input size {}x{} is not a multiple of the model stride {}.",
+                height, width, stride
+            );
+        }
+        let task = config.task.clone();
 
         // Task & layout
         let version = config.version;
@@ -303,7 +304,14 @@ impl YOLO {
     }
 
     /// Performs model inference on the pre-processed input.
+    ///
+    /// Only instance segmentation reads the mask protos, so other tasks fetch the predictions alone.
     fn inference(&mut self, xs: Xs) -> Result<Xs> {
+        if !matches!(self.task, Task::InstanceSegmentation) {
+            if let Some(name) = self.engine.onames().and_then(|x| x.first()).cloned() {
+                return self.engine.run_with_outputs(xs, &[&name]);
+            }
+        }
         self.engine.run(xs)
     }
 
@@ -314,6 +322,11 @@ impl YOLO {
     /// 2. Running model inference
     /// 3. Post-processing the outputs to generate final predictions
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         // Forward pass
         let ys = elapsed_module!("YOLO", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("YOLO", "inference", self.inference(ys)?);
@@ -626,6 +639,77 @@ impl YOLO {
         // Ok(ys.into())
     }
 
+    /// Fills the task and version left unset in the config from the ONNX model metadata.
+    fn fill_config_from_onnx(mut config: Config, engine: &Engine) -> Config {
+        if config.task.is_none() {
+            config.task = match engine.try_fetch("task") {
+                Some(x) => match x.as_str() {
+                    "classify" => Some(Task::ImageClassification),
+                    "detect" => Some(Task::ObjectDetection),
+                    "pose" => Some(Task::KeypointsDetection),
+                    "segment" => Some(Task::InstanceSegmentation),
+                    "obb" => Some(Task::OrientedObjectDetection),
+                    x => {
+                        error!("Unsupported YOLO Task: {}", x);
+                        None
+                    }
+                },
+                None => None,
+            };
+        }
+        if config.version.is_none() && config.yolo_preds_format.is_none() {
+            config.version = Self::fetch_version_from_onnx(engine);
+        }
+
+        config
+    }
+
+    /// Extracts the YOLO version from the ONNX model metadata if available.
+    fn fetch_version_from_onnx(engine: &Engine) -> Option<Version> {
+        // String format: `Ultralytics YOLOv8n-seg model trained on coco.yaml`, `Ultralytics YOLO11n model ...`
+        Regex::new(r"YOLO\s?v?(\d+)")
+            .ok()?
+            .captures(&engine.try_fetch("description")?)
+            .and_then(|caps| caps.get(1))
+            .and_then(|m| m.as_str().parse::<u8>().ok())
+            // `YOLOv3u`, `YOLOv5u` and `YOLOv6` exported by Ultralytics share the v8 head
+            .map(|x| Version::from(x.max(8)))
+    }
+
+    /// Fills the input height and width left unset in the config from the `imgsz` metadata,
+    /// or 640 without it, rounded up to a multiple of the model stride.
+    fn fill_imgsz_from_onnx(mut config: Config, proto: &onnx::ModelProto, stride: usize) -> Config {
+        let imgsz: Vec<usize> = Engine::fetch_from_proto(proto, "imgsz")
+            .map(|x| {
+                x.trim_matches(|c| c == '[' || c == ']')
+                    .split(',')
+                    .filter_map(|x| x.trim().parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (height, width) = match imgsz[..] {
+            [x] => (x, x),
+            [h, w] => (h, w),
+            _ => (640, 640),
+        };
+        for (ii, x) in [(2, height), (3, width)] {
+            if !config.model.iiixs.iter().any(|x| x.i == 0 && x.ii == ii) {
+                config = config.with_model_ixx(0, ii, (x.div_ceil(stride) * stride).into());
+            }
+        }
+
+        config
+    }
+
+    /// Extracts the model stride from the ONNX model metadata if available.
+    fn fetch_stride_from_onnx(proto: &onnx::ModelProto) -> Option<usize> {
+        Engine::fetch_from_proto(proto, "stride")?
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&x| x > 0)
+    }
+
     /// Extracts class names from the ONNX model metadata if available.
     fn fetch_names_from_onnx(engine: &Engine) -> Option<Vec<String>> {
         // fetch class names from onnx metadata
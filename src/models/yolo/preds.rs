@@ -1,9 +1,10 @@
 use ndarray::{ArrayBase, ArrayView, Axis, Dim, IxDyn, IxDynImpl, ViewRepr};
+use serde::{Deserialize, Serialize};
 
 use crate::Task;
 
 /// Bounding box coordinate format types.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BoxType {
     Cxcywh,
     Cxcyxy,
@@ -13,7 +14,7 @@ pub enum BoxType {
 }
 
 /// Classification output format types.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ClssType {
     Clss,
     ConfCls,
@@ -23,21 +24,22 @@ pub enum ClssType {
 }
 
 /// Keypoint output format types.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum KptsType {
     Xys,
     Xycs,
 }
 
 /// Anchor position in the prediction pipeline.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AnchorsPosition {
     Before,
     After,
 }
 
 /// YOLO prediction format configuration.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct YOLOPredsFormat {
     pub clss: ClssType,
     pub bbox: Option<BoxType>,
@@ -3,7 +3,8 @@ use anyhow::Result;
 use ndarray::{s, Array, Axis, IxDyn};
 
 use crate::{
-    elapsed_module, Config, DynConf, Engine, Hbb, Image, NmsOps, Ops, Polygon, Processor, Xs, Y,
+    elapsed_module, forward_in_batches, Config, DynConf, Engine, Hbb, Image, NmsOps, Ops, Polygon,
+    Processor, Xs, Y,
 };
 
 #[derive(Builder, Debug)]
@@ -56,6 +57,11 @@ impl YOLOPv2 {
     }
 
     pub fn forward(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
+        let max_batch = self.engine.max_batch();
+        forward_in_batches(xs, max_batch, |xs| self.forward_batch(xs))
+    }
+
+    fn forward_batch(&mut self, xs: &[Image]) -> Result<Vec<Y>> {
         let ys = elapsed_module!("YOLOPv2", "preprocess", self.preprocess(xs)?);
         let ys = elapsed_module!("YOLOPv2", "inference", self.inference(ys)?);
         let ys = elapsed_module!("YOLOPv2", "postprocess", self.postprocess(ys)?);
@@ -1,6 +1,8 @@
 use aksr::Builder;
 
-use crate::{Hbb, Image, Keypoint, Mask, Obb, Polygon, Prob, Text, Heatmap};
+use crate::{
+    FrameMeta, Hbb, Heatmap, Image, Interruption, Keypoint, Mask, Obb, Polygon, Prob, Text,
+};
 
 /// Container for inference results for each image.
 ///
@@ -20,6 +22,8 @@ pub struct Y {
     masks: Option<Vec<Mask>>,
     images: Option<Vec<Image>>,
     heatmaps: Option<Vec<Heatmap>>,
+    interruption: Option<Interruption>,
+    frame: Option<FrameMeta>,
 }
 
 impl std::fmt::Debug for Y {
@@ -68,6 +72,12 @@ impl std::fmt::Debug for Y {
                 f.field("Images", &xs);
             }
         }
+        if let Some(x) = &self.interruption {
+            f.field("Interruption", x);
+        }
+        if let Some(x) = &self.frame {
+            f.field("Frame", x);
+        }
         f.finish()
     }
 }